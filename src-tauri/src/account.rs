@@ -27,10 +27,14 @@ pub struct Account {
     pub client_id_hash: Option<String>,
     pub sso_session_id: Option<String>,
     pub id_token: Option<String>,
+    // IdC 客户端注册过期时间 (Unix 秒，来自 RegisterClient 的 clientSecretExpiresAt)
+    pub client_secret_expires_at: Option<i64>,
     // Social 专用
     pub profile_arn: Option<String>,
     // 原始 usage API 响应
     pub usage_data: Option<serde_json::Value>,
+    // 切换到该账号时应用的 Kiro 设置配置 ID
+    pub settings_profile_id: Option<String>,
 }
 
 
@@ -56,8 +60,19 @@ impl Account {
             client_id_hash: None,
             sso_session_id: None,
             id_token: None,
+            client_secret_expires_at: None,
             profile_arn: None,
             usage_data: None,
+            settings_profile_id: None,
+        }
+    }
+
+    /// IdC 客户端注册是否会在 within_secs 秒内过期（已过期也返回 true）
+    /// 没有记录过期时间的账号返回 false
+    pub fn idc_client_expires_within(&self, within_secs: i64) -> bool {
+        match (self.client_id.as_ref(), self.client_secret_expires_at) {
+            (Some(_), Some(expires_at)) => expires_at - chrono::Utc::now().timestamp() <= within_secs,
+            _ => false,
         }
     }
 }
@@ -74,6 +89,13 @@ impl AccountStore {
         Self { accounts, file_path }
     }
 
+    /// 使用指定文件的账号存储（测试用临时文件）
+    #[cfg(test)]
+    pub fn with_path(file_path: PathBuf) -> Self {
+        let accounts = Self::load_from_file(&file_path);
+        Self { accounts, file_path }
+    }
+
     fn get_storage_path() -> PathBuf {
         let data_dir = dirs::data_dir().unwrap_or_else(|| {
             let home = std::env::var("USERPROFILE")
@@ -1,5 +1,6 @@
 // Auth 模块 - 当前使用的认证相关代码
 
+use crate::http_client::RetryPolicy;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 
@@ -38,8 +39,7 @@ impl AuthState {
 // API 常量
 // ============================================================
 
-pub const DESKTOP_AUTH_API: &str = "https://prod.us-east-1.auth.desktop.kiro.dev";
-const DESKTOP_USAGE_API: &str = "https://codewhisperer.us-east-1.amazonaws.com";
+// 服务地址见 endpoints.rs（desktop_auth / codewhisperer）
 const PROFILE_ARN: &str = "arn:aws:codewhisperer:us-east-1:699475941385:profile/EHGA3GRVQMUK";
 
 // ============================================================
@@ -125,7 +125,7 @@ pub struct BonusInfo {
 
 /// 使用桌面端 API 刷新 Token（只需要 RefreshToken）
 pub async fn refresh_token_desktop(refresh_token: &str) -> Result<DesktopRefreshResponse, String> {
-    let client = reqwest::Client::builder()
+    let client = crate::http_client::client_builder(None)
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| format!("Failed to create client: {}", e))?;
@@ -134,68 +134,53 @@ pub async fn refresh_token_desktop(refresh_token: &str) -> Result<DesktopRefresh
         "refreshToken": refresh_token
     });
     
-    // 重试机制
-    let mut last_error = String::new();
-    for attempt in 0..3 {
-        if attempt > 0 {
-            tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
-        }
-        
-        match client
-            .post(format!("{}/refreshToken", DESKTOP_AUTH_API))
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(&body)
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_default();
-                
-                println!("\n[Desktop] RefreshToken Response:");
-                println!("Status: {}", status);
-                // 格式化打印 JSON
-                match serde_json::from_str::<serde_json::Value>(&text) {
-                    Ok(json) => {
-                        match serde_json::to_string_pretty(&json) {
-                            Ok(pretty) => println!("{}", pretty),
-                            Err(_) => println!("{}", text),
-                        }
-                    }
-                    Err(_) => println!("{}", text),
-                }
-                
-                if !status.is_success() {
-                    if status.as_u16() == 401 {
-                        return Err("RefreshToken 已过期或无效".to_string());
-                    }
-                    return Err(format!("RefreshToken failed ({})", status));
-                }
-                
-                return serde_json::from_str(&text)
-                    .map_err(|e| format!("Parse failed: {}", e));
-            }
-            Err(e) => {
-                last_error = format!("网络错误: {}", e);
-                continue;
+    // 刷新会轮换 RefreshToken，按非幂等策略重试（仅连接失败 / 限流）
+    let request = client
+        .post(format!("{}/refreshToken", crate::endpoints::endpoints().desktop_auth))
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .json(&body);
+    let response = crate::http_client::send_with_retry(request, RetryPolicy::NonIdempotent, "desktop.refreshToken")
+        .await
+        .map_err(|e| format!("网络错误: {}", e))?;
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    
+    println!("\n[Desktop] RefreshToken Response:");
+    println!("Status: {}", status);
+    // 格式化打印 JSON
+    match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(json) => {
+            match serde_json::to_string_pretty(&json) {
+                Ok(pretty) => println!("{}", pretty),
+                Err(_) => println!("{}", text),
             }
         }
+        Err(_) => println!("{}", text),
+    }
+    
+    if !status.is_success() {
+        if status.as_u16() == 401 {
+            return Err("RefreshToken 已过期或无效".to_string());
+        }
+        return Err(format!("RefreshToken failed ({})", status));
     }
     
-    Err(last_error)
+    serde_json::from_str(&text)
+        .map_err(|e| format!("Parse failed: {}", e))
 }
 
 /// 使用桌面端 API 获取配额和用户信息
 pub async fn get_usage_limits_desktop(access_token: &str) -> Result<DesktopUsageResponse, String> {
-    let client = reqwest::Client::builder()
+    let client = crate::http_client::client_builder(None)
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| format!("Failed to create client: {}", e))?;
     
     let url = format!(
         "{}/getUsageLimits?isEmailRequired=true&origin=AI_EDITOR&profileArn={}",
-        DESKTOP_USAGE_API,
+        crate::endpoints::endpoints().codewhisperer,
         urlencoding::encode(PROFILE_ARN)
     );
 
@@ -203,57 +188,41 @@ pub async fn get_usage_limits_desktop(access_token: &str) -> Result<DesktopUsage
     // println!("URL: {}", url);
     // println!("Token: {}", access_token);
     
-    // 重试机制
-    let mut last_error = String::new();
-    for attempt in 0..3 {
-        if attempt > 0 {
-            tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
-        }
-        
-        match client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", access_token))
-            .header("Accept", "application/json")
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_default();
-                
-                println!("\n[Social] GET USAGE LIMITS RESPONSE");
-                println!("Status: {}", status);
-                // 格式化打印 JSON
-                match serde_json::from_str::<serde_json::Value>(&text) {
-                    Ok(json) => {
-                        match serde_json::to_string_pretty(&json) {
-                            Ok(pretty) => println!("{}", pretty),
-                            Err(_) => println!("{}", text),
-                        }
-                    }
-                    Err(_) => println!("{}", text),
-                }
-                println!();
-                
-                if !status.is_success() {
-                    // 解析错误响应，提取 reason 字段
-                    if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(&text) {
-                        if let Some(reason) = error_json.get("reason").and_then(|r| r.as_str()) {
-                            return Err(format!("BANNED:{}", reason));
-                        }
-                    }
-                    return Err(format!("GetUsageLimits failed ({})", status));
-                }
-                
-                return serde_json::from_str(&text)
-                    .map_err(|e| format!("Parse failed: {}", e));
+    let request = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .header("Accept", "application/json");
+    let response = crate::http_client::send_with_retry(request, RetryPolicy::Idempotent, "desktop.getUsageLimits")
+        .await
+        .map_err(|e| format!("网络错误: {}", e))?;
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    
+    println!("\n[Social] GET USAGE LIMITS RESPONSE");
+    println!("Status: {}", status);
+    // 格式化打印 JSON
+    match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(json) => {
+            match serde_json::to_string_pretty(&json) {
+                Ok(pretty) => println!("{}", pretty),
+                Err(_) => println!("{}", text),
             }
-            Err(e) => {
-                last_error = format!("网络错误: {}", e);
-                continue;
+        }
+        Err(_) => println!("{}", text),
+    }
+    println!();
+    
+    if !status.is_success() {
+        // 解析错误响应，提取 reason 字段
+        if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(&text) {
+            if let Some(reason) = error_json.get("reason").and_then(|r| r.as_str()) {
+                return Err(format!("BANNED:{}", reason));
             }
         }
+        return Err(format!("GetUsageLimits failed ({})", status));
     }
     
-    Err(last_error)
+    serde_json::from_str(&text)
+        .map_err(|e| format!("Parse failed: {}", e))
 }
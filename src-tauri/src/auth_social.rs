@@ -1,4 +1,5 @@
-use crate::auth::{DesktopRefreshResponse, DESKTOP_AUTH_API};
+use crate::auth::DesktopRefreshResponse;
+use crate::http_client::{send_with_retry, RetryPolicy};
 
 /// 生成PKCE code_verifier（32字节，base64url）
 pub fn generate_code_verifier_social() -> String {
@@ -29,7 +30,7 @@ pub async fn exchange_social_code_for_token(
     redirect_uri: &str,
     machineid: &str,
 ) -> Result<DesktopRefreshResponse, String> {
-    let client = reqwest::Client::new();
+    let client = crate::http_client::build_client()?;
     let body = serde_json::json!({
         "code": code,
         "code_verifier": code_verifier,
@@ -39,13 +40,13 @@ pub async fn exchange_social_code_for_token(
     let kiro_ide_version = "0.6.18";
     let user_agent = format!("KiroIDE-{}-{}", kiro_ide_version, machineid);
 
-    let response = client
-        .post(format!("{}/oauth/token", DESKTOP_AUTH_API))
+    let request = client
+        .post(format!("{}/oauth/token", crate::endpoints::endpoints().desktop_auth))
         .header("Content-Type", "application/json")
         .header("Accept", "application/json")
         .header("user-agent", user_agent)
-        .json(&body)
-        .send()
+        .json(&body);
+    let response = send_with_retry(request, RetryPolicy::NonIdempotent, "desktop.oauthToken")
         .await
         .map_err(|e| format!("OAuth token request failed: {}", e))?;
 
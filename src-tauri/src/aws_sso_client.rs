@@ -1,6 +1,7 @@
 /// AWS SSO OIDC Client
 /// 实现 AWS SSO OIDC API 调用，用于 BuilderId 认证
 
+use crate::http_client::{send_with_retry, RetryPolicy};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
@@ -52,8 +53,8 @@ pub struct TokenResponse {
 
 impl AWSSSOClient {
     pub fn new(region: &str) -> Self {
-        let base_url = format!("https://oidc.{}.amazonaws.com", region);
-        let client = Client::builder()
+        let base_url = crate::endpoints::endpoints().oidc_url(region);
+        let client = crate::http_client::client_builder(None)
             .timeout(Duration::from_secs(30))
             .build()
             .expect("Failed to create HTTP client");
@@ -83,11 +84,11 @@ impl AWSSSOClient {
 
         println!("\n[AWS SSO] Refresh Token");
 
-        let resp = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .json(&body);
+        let resp = send_with_retry(request, RetryPolicy::NonIdempotent, "ssoOidc.refreshToken")
             .await
             .map_err(|e| format!("Token refresh request failed: {}", e))?;
 
@@ -95,6 +96,11 @@ impl AWSSSOClient {
         let text = resp.text().await.unwrap_or_default();
 
         if !status.is_success() {
+            // 客户端注册过期: invalid_client / InvalidClientException
+            // 返回特殊格式: "CLIENT_EXPIRED:..." 便于上层触发重新注册
+            if text.contains("invalid_client") || text.contains("InvalidClientException") {
+                return Err(format!("CLIENT_EXPIRED:{}", text));
+            }
             if status.as_u16() == 401 {
                 return Err("RefreshToken 已过期或无效".to_string());
             }
@@ -127,11 +133,11 @@ impl AWSSSOClient {
 
         println!("\n[AWS SSO] Register Device Client (region: {})", self.region);
 
-        let resp = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .json(&body);
+        let resp = send_with_retry(request, RetryPolicy::NonIdempotent, "ssoOidc.registerClient")
             .await
             .map_err(|e| format!("Device client registration failed: {}", e))?;
 
@@ -164,11 +170,11 @@ impl AWSSSOClient {
 
         println!("\n[AWS SSO] Start Device Authorization");
 
-        let resp = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .json(&body);
+        let resp = send_with_retry(request, RetryPolicy::NonIdempotent, "ssoOidc.startDeviceAuthorization")
             .await
             .map_err(|e| format!("Device authorization failed: {}", e))?;
 
@@ -200,11 +206,11 @@ impl AWSSSOClient {
             "deviceCode": device_code
         });
 
-        let resp = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .json(&body);
+        let resp = send_with_retry(request, RetryPolicy::NonIdempotent, "ssoOidc.pollDeviceToken")
             .await
             .map_err(|e| format!("Device token poll failed: {}", e))?;
 
@@ -137,7 +137,7 @@ impl BtMailClient {
         Self {
             url: BT_PANEL_URL.trim_end_matches('/').to_string(),
             key: BT_API_KEY.to_string(),
-            client: reqwest::Client::builder()
+            client: crate::http_client::client_builder(None)
                 .timeout(std::time::Duration::from_secs(15))
                 .danger_accept_invalid_certs(true)
                 .build()
@@ -1,13 +1,12 @@
 // CodeWhisperer API Client
 // 用于 IdC (BuilderId) 账号获取限额信息
 
+use crate::http_client::{send_with_retry, RetryPolicy};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use uuid::Uuid;
 
-const CODEWHISPERER_API: &str = "https://codewhisperer.us-east-1.amazonaws.com";
-
 /// CodeWhisperer 限额响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -97,7 +96,7 @@ pub struct CodeWhispererClient {
 
 impl CodeWhispererClient {
     pub fn new(machine_id: &str) -> Self {
-        let client = Client::builder()
+        let client = crate::http_client::client_builder(None)
             .timeout(Duration::from_secs(30))
             .build()
             .expect("Failed to create HTTP client");
@@ -117,7 +116,7 @@ impl CodeWhispererClient {
     pub async fn get_usage_limits(&self, access_token: &str) -> Result<CodeWhispererUsageResponse, String> {
         let url = format!(
             "{}/getUsageLimits?isEmailRequired=true&origin=AI_EDITOR&resourceType=AGENTIC_REQUEST",
-            CODEWHISPERER_API
+            crate::endpoints::endpoints().codewhisperer
         );
 
         let kiro_version = "0.6.18";
@@ -130,15 +129,15 @@ impl CodeWhispererClient {
         println!("\n[CodeWhisperer] GET USAGE LIMITS");
         println!("URL: {}", url);
 
-        let response = self.client
+        let request = self.client
             .get(&url)
             .header("Authorization", format!("Bearer {}", access_token))
             .header("x-amz-user-agent", &x_amz_user_agent)
             .header("user-agent", &user_agent)
             .header("amz-sdk-invocation-id", Self::generate_invocation_id())
             .header("amz-sdk-request", "attempt=1; max=1")
-            .header("Connection", "close")
-            .send()
+            .header("Connection", "close");
+        let response = send_with_retry(request, RetryPolicy::Idempotent, "codewhisperer.getUsageLimits")
             .await
             .map_err(|e| format!("Request failed: {}", e))?;
 
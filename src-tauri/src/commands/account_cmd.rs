@@ -1,6 +1,6 @@
 // 账号相关命令 - 直接存储原始 usage_data
 
-use tauri::State;
+use tauri::{Emitter, State};
 use crate::state::AppState;
 use crate::account::Account;
 use crate::auth::{User, refresh_token_desktop, get_usage_limits_desktop};
@@ -9,6 +9,9 @@ use crate::providers::{AuthProvider, SocialProvider, IdcProvider, RefreshMetadat
 use crate::kiro::get_machine_id;
 use serde::{Deserialize, Serialize};
 
+/// IdC 客户端注册提前提醒时间（7 天）
+const IDC_CLIENT_EXPIRY_WARNING_SECS: i64 = 7 * 24 * 3600;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerifyAccountResponse {
     #[serde(rename = "usageLimit")]
@@ -38,8 +41,20 @@ pub fn delete_accounts(state: State<AppState>, ids: Vec<String>) -> usize {
     state.store.lock().unwrap().delete_many(&ids)
 }
 
+/// 刷新失败且是客户端注册过期时，标记账号状态
+fn mark_idc_client_expired(state: &State<'_, AppState>, id: &str, err: &str) {
+    if !err.starts_with("CLIENT_EXPIRED:") {
+        return;
+    }
+    let mut store = state.store.lock().unwrap();
+    if let Some(a) = store.accounts.iter_mut().find(|a| a.id == id) {
+        a.status = "客户端已过期".to_string();
+        store.save_to_file();
+    }
+}
+
 #[tauri::command]
-pub async fn sync_account(state: State<'_, AppState>, id: String) -> Result<Account, String> {
+pub async fn sync_account<R: tauri::Runtime>(app_handle: tauri::AppHandle<R>, state: State<'_, AppState>, id: String) -> Result<Account, String> {
     let account = {
         let store = state.store.lock().unwrap();
         store.accounts.iter().find(|a| a.id == id).cloned()
@@ -62,7 +77,8 @@ pub async fn sync_account(state: State<'_, AppState>, id: String) -> Result<Acco
                 ..Default::default()
             };
             let idc_provider = IdcProvider::new("BuilderId", metadata.region.as_deref().unwrap_or("us-east-1"), None);
-            let auth_result = idc_provider.refresh_token(refresh_token_str, metadata).await?;
+            let auth_result = idc_provider.refresh_token(refresh_token_str, metadata).await
+                .inspect_err(|e| mark_idc_client_expired(&state, &id, e))?;
             (auth_result.access_token, Some(auth_result.refresh_token), auth_result.expires_in, None, auth_result.id_token, auth_result.sso_session_id)
         } else {
             // Google/Github (Desktop OAuth 或 Web OAuth) -> Desktop API
@@ -122,6 +138,12 @@ pub async fn sync_account(state: State<'_, AppState>, id: String) -> Result<Acco
         
         let result = a.clone();
         store.save_to_file();
+        drop(store);
+
+        // IdC 客户端注册即将过期，通知前端引导重新授权
+        if result.idc_client_expires_within(IDC_CLIENT_EXPIRY_WARNING_SECS) {
+            let _ = app_handle.emit("idc-client-expiring", IdcClientStatus::from_account(&result));
+        }
         return Ok(result);
     }
 
@@ -150,7 +172,8 @@ pub async fn refresh_account_token(state: State<'_, AppState>, id: String) -> Re
                 ..Default::default()
             };
             let idc_provider = IdcProvider::new("BuilderId", metadata.region.as_deref().unwrap_or("us-east-1"), None);
-            let auth_result = idc_provider.refresh_token(refresh_token_str, metadata).await?;
+            let auth_result = idc_provider.refresh_token(refresh_token_str, metadata).await
+                .inspect_err(|e| mark_idc_client_expired(&state, &id, e))?;
             (auth_result.access_token, Some(auth_result.refresh_token), auth_result.expires_in)
         } else {
             let metadata = RefreshMetadata {
@@ -392,12 +415,14 @@ pub async fn add_local_kiro_account(state: State<'_, AppState>) -> Result<Accoun
         let client_reg = get_client_registration(&hash)
             .ok_or(format!("未找到客户端注册信息: {}.json", hash))?;
         
+        let client_secret_expires_at = client_reg.expires_at_timestamp();
         add_account_by_idc(
             state,
             refresh_token,
             client_reg.client_id,
             client_reg.client_secret,
             Some(region),
+            client_secret_expires_at,
         ).await
     } else {
         add_account_by_social(
@@ -416,6 +441,7 @@ pub async fn add_account_by_idc(
     client_id: String,
     client_secret: String,
     region: Option<String>,
+    client_secret_expires_at: Option<i64>,
 ) -> Result<Account, String> {
     let region = region.unwrap_or_else(|| "us-east-1".to_string());
     let metadata = RefreshMetadata {
@@ -464,6 +490,9 @@ pub async fn add_account_by_idc(
         existing.expires_at = Some(expires_at.format("%Y/%m/%d %H:%M:%S").to_string());
         existing.client_id = Some(client_id);
         existing.client_secret = Some(client_secret);
+        if client_secret_expires_at.is_some() {
+            existing.client_secret_expires_at = client_secret_expires_at;
+        }
         existing.region = Some(region);
         existing.client_id_hash = Some(client_id_hash);
         existing.id_token = auth_result.id_token;
@@ -480,6 +509,7 @@ pub async fn add_account_by_idc(
         account.expires_at = Some(expires_at.format("%Y/%m/%d %H:%M:%S").to_string());
         account.client_id = Some(client_id);
         account.client_secret = Some(client_secret);
+        account.client_secret_expires_at = client_secret_expires_at;
         account.region = Some(region);
         account.client_id_hash = Some(client_id_hash);
         account.id_token = auth_result.id_token;
@@ -536,3 +566,132 @@ pub fn update_account(
         Err("账号不存在".to_string())
     }
 }
+
+// ============================================================
+// IdC 客户端注册过期管理
+// ============================================================
+
+/// IdC 客户端注册状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdcClientStatus {
+    pub account_id: String,
+    pub email: String,
+    pub client_secret_expires_at: Option<i64>,
+    pub expired: bool,
+    pub expiring_soon: bool,
+}
+
+impl IdcClientStatus {
+    fn from_account(account: &Account) -> Self {
+        Self {
+            account_id: account.id.clone(),
+            email: account.email.clone(),
+            client_secret_expires_at: account.client_secret_expires_at,
+            expired: account.idc_client_expires_within(0) || account.status == "客户端已过期",
+            expiring_soon: account.idc_client_expires_within(IDC_CLIENT_EXPIRY_WARNING_SECS),
+        }
+    }
+}
+
+/// 获取所有 IdC 账号的客户端注册状态
+#[tauri::command]
+pub fn get_idc_client_status(state: State<AppState>) -> Vec<IdcClientStatus> {
+    let store = state.store.lock().unwrap();
+    store.accounts.iter()
+        .filter(|a| a.client_id.is_some())
+        .map(IdcClientStatus::from_account)
+        .collect()
+}
+
+/// 重新注册 IdC 客户端并通过设备授权迁移账号
+/// 旧客户端签发的 refresh_token 无法转移到新客户端，因此需要用户在浏览器中重新授权
+#[tauri::command]
+pub async fn reauthorize_idc_account(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Account, String> {
+    let account = {
+        let store = state.store.lock().unwrap();
+        store.accounts.iter().find(|a| a.id == id).cloned()
+    }.ok_or("Account not found")?;
+
+    if account.client_id.is_none() {
+        return Err("该账号不是 IdC 账号".to_string());
+    }
+
+    let provider = account.provider.clone().unwrap_or_else(|| "BuilderId".to_string());
+    let region = account.region.clone().unwrap_or_else(|| "us-east-1".to_string());
+
+    println!("[IdC] Re-registering client for {}", account.email);
+    let _ = app_handle.emit("idc-reauth-started", &id);
+
+    // 注册新客户端 + 设备授权（会打开浏览器）
+    let idc_provider = IdcProvider::new(&provider, &region, None);
+    let auth_result = idc_provider.login().await
+        .inspect_err(|e| { let _ = app_handle.emit("idc-reauth-failed", e); })?;
+
+    // 确认授权的是同一个用户
+    let machine_id = get_machine_id();
+    let cw_client = CodeWhispererClient::new(&machine_id);
+    let usage = cw_client.get_usage_limits(&auth_result.access_token).await.ok();
+    if let Some(email) = usage.as_ref()
+        .and_then(|u| u.user_info.as_ref())
+        .and_then(|u| u.email.clone())
+    {
+        if email != account.email {
+            let msg = format!("授权的账号 {} 与原账号 {} 不一致", email, account.email);
+            let _ = app_handle.emit("idc-reauth-failed", &msg);
+            return Err(msg);
+        }
+    }
+
+    let client_id = auth_result.client_id.clone().ok_or("注册客户端失败: 缺少 client_id")?;
+    let client_secret = auth_result.client_secret.clone().ok_or("注册客户端失败: 缺少 client_secret")?;
+    let client_id_hash = auth_result.client_id_hash.clone()
+        .or(account.client_id_hash.clone())
+        .unwrap_or_default();
+
+    // IDE 正在使用该账号时同步更新 IDE 侧文件
+    if let Some(old_refresh_token) = account.refresh_token.as_deref() {
+        match crate::kiro::sync_idc_credentials_to_ide(
+            old_refresh_token,
+            &client_id_hash,
+            &client_id,
+            &client_secret,
+            auth_result.client_secret_expires_at,
+            &auth_result.access_token,
+            &auth_result.refresh_token,
+        ) {
+            Ok(updated) => println!("[IdC] IDE credentials synced: {}", updated),
+            Err(e) => println!("[IdC] Failed to sync IDE credentials: {}", e),
+        }
+    }
+
+    let mut store = state.store.lock().unwrap();
+    let a = store.accounts.iter_mut().find(|a| a.id == id)
+        .ok_or("Account not found after re-authorization")?;
+    a.access_token = Some(auth_result.access_token);
+    a.refresh_token = Some(auth_result.refresh_token);
+    a.expires_at = Some(auth_result.expires_at);
+    a.client_id = Some(client_id);
+    a.client_secret = Some(client_secret);
+    a.client_id_hash = Some(client_id_hash);
+    a.client_secret_expires_at = auth_result.client_secret_expires_at;
+    a.region = auth_result.region;
+    a.id_token = auth_result.id_token;
+    a.sso_session_id = auth_result.sso_session_id;
+    if let Some(u) = usage {
+        a.usage_data = Some(serde_json::to_value(&u).unwrap_or(serde_json::Value::Null));
+    }
+    a.status = "正常".to_string();
+
+    let result = a.clone();
+    store.save_to_file();
+    drop(store);
+
+    println!("[IdC] Client re-registered for {}", result.email);
+    let _ = app_handle.emit("idc-reauth-finished", &id);
+    Ok(result)
+}
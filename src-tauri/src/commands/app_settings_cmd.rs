@@ -17,6 +17,24 @@ pub struct AppSettings {
     pub bind_machine_id_to_account: Option<bool>,  // 是否启用账户绑定机器码
     pub use_bound_machine_id: Option<bool>,        // 切换时使用绑定的机器码（否则随机生成）
     pub account_machine_ids: Option<std::collections::HashMap<String, String>>,  // 账户ID -> 机器码映射
+    // 网络设置（所有出站 HTTP 客户端共用）
+    pub proxy_mode: Option<String>,                // off / custom / system / kiro（默认 kiro）
+    pub proxy_url: Option<String>,                 // proxy_mode = custom 时使用
+    pub extra_ca_certs: Option<Vec<String>>,       // 额外信任的根证书（PEM 文件路径）
+    pub http_connect_timeout_secs: Option<u64>,
+    pub http_read_timeout_secs: Option<u64>,
+    pub http_user_agent: Option<String>,           // 覆盖默认 User-Agent
+    pub endpoints: Option<crate::endpoints::EndpointOverrides>,  // 服务端点覆盖（staging / 本地 mock）
+    // Kiro IDE 安装路径（可执行文件 / AppImage / 安装目录），优先于自动探测
+    pub kiro_install_paths: Option<Vec<String>>,
+    pub kiro_shutdown_timeout_secs: Option<u64>,   // 关闭 IDE 时等待正常退出的秒数，超时后强制结束
+    pub kiro_data_dir: Option<String>,             // 覆盖 Kiro 用户数据目录（包含 User/settings.json）
+    // 固定的 Kiro 设置项（settings.json 顶层键 -> 值），被外部修改后自动恢复
+    pub pinned_kiro_settings: Option<serde_json::Map<String, serde_json::Value>>,
+    // 多个 Kiro 安装（手动登记的），以及默认操作的安装 ID（空字符串表示自动探测）
+    pub kiro_installations: Option<Vec<crate::installations::KiroInstallation>>,
+    pub active_kiro_installation: Option<String>,
+    pub close_to_tray: Option<bool>,               // 关闭窗口时隐藏到托盘（后台刷新继续运行）
 }
 
 fn get_app_settings_path() -> PathBuf {
@@ -43,7 +61,7 @@ fn get_app_settings_inner() -> Result<AppSettings, String> {
         .map_err(|e| format!("解析设置失败: {}", e))
 }
 
-fn save_app_settings_inner(updates: AppSettings) -> Result<(), String> {
+pub(crate) fn save_app_settings_inner(updates: AppSettings) -> Result<(), String> {
     let path = get_app_settings_path();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).ok();
@@ -63,6 +81,20 @@ fn save_app_settings_inner(updates: AppSettings) -> Result<(), String> {
     if updates.bind_machine_id_to_account.is_some() { current.bind_machine_id_to_account = updates.bind_machine_id_to_account; }
     if updates.use_bound_machine_id.is_some() { current.use_bound_machine_id = updates.use_bound_machine_id; }
     if updates.account_machine_ids.is_some() { current.account_machine_ids = updates.account_machine_ids; }
+    if updates.proxy_mode.is_some() { current.proxy_mode = updates.proxy_mode; }
+    if updates.proxy_url.is_some() { current.proxy_url = updates.proxy_url; }
+    if updates.extra_ca_certs.is_some() { current.extra_ca_certs = updates.extra_ca_certs; }
+    if updates.http_connect_timeout_secs.is_some() { current.http_connect_timeout_secs = updates.http_connect_timeout_secs; }
+    if updates.http_read_timeout_secs.is_some() { current.http_read_timeout_secs = updates.http_read_timeout_secs; }
+    if updates.http_user_agent.is_some() { current.http_user_agent = updates.http_user_agent; }
+    if updates.endpoints.is_some() { current.endpoints = updates.endpoints; }
+    if updates.kiro_install_paths.is_some() { current.kiro_install_paths = updates.kiro_install_paths; }
+    if updates.kiro_shutdown_timeout_secs.is_some() { current.kiro_shutdown_timeout_secs = updates.kiro_shutdown_timeout_secs; }
+    if updates.kiro_data_dir.is_some() { current.kiro_data_dir = updates.kiro_data_dir; }
+    if updates.pinned_kiro_settings.is_some() { current.pinned_kiro_settings = updates.pinned_kiro_settings; }
+    if updates.kiro_installations.is_some() { current.kiro_installations = updates.kiro_installations; }
+    if updates.active_kiro_installation.is_some() { current.active_kiro_installation = updates.active_kiro_installation; }
+    if updates.close_to_tray.is_some() { current.close_to_tray = updates.close_to_tray; }
     
     let content = serde_json::to_string_pretty(&current)
         .map_err(|e| format!("序列化失败: {}", e))?;
@@ -85,6 +117,11 @@ pub async fn save_app_settings(settings: AppSettings) -> Result<(), String> {
         .map_err(|e| format!("Task failed: {}", e))?
 }
 
+/// 读取应用设置（供其他模块使用，失败时返回默认值）
+pub fn load_app_settings() -> AppSettings {
+    get_app_settings_inner().unwrap_or_default()
+}
+
 /// 获取自定义浏览器路径（供打开浏览器时使用）
 pub fn get_browser_path() -> Option<String> {
     let path = get_app_settings_path();
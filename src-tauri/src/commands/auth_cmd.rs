@@ -142,6 +142,7 @@ async fn login_idc(
         existing.client_secret = auth_result.client_secret;
         existing.region = auth_result.region;
         existing.sso_session_id = auth_result.sso_session_id;
+        existing.client_secret_expires_at = auth_result.client_secret_expires_at;
         existing.id_token = auth_result.id_token;
         existing.profile_arn = auth_result.profile_arn;
         existing.usage_data = Some(usage_data);
@@ -159,6 +160,7 @@ async fn login_idc(
         account.client_secret = auth_result.client_secret;
         account.region = auth_result.region;
         account.sso_session_id = auth_result.sso_session_id;
+        account.client_secret_expires_at = auth_result.client_secret_expires_at;
         account.id_token = auth_result.id_token;
         account.profile_arn = auth_result.profile_arn;
         account.usage_data = Some(usage_data);
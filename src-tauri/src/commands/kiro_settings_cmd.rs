@@ -1,7 +1,16 @@
 // Kiro IDE 设置命令 (读写 Kiro IDE 的 settings.json)
+// settings.json 是 JSONC，写入时只修改目标键，保留用户的注释和格式；
+// 文件无法解析时拒绝写入，每次写入前把旧版本备份到 ~/.kiro-account-manager/backups/kiro-settings
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::installations::with_installation;
+
+/// 最多保留的备份数量
+const MAX_SETTINGS_BACKUPS: usize = 20;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -10,44 +19,135 @@ pub struct KiroSettings {
     pub model_selection: Option<String>,
 }
 
-fn get_kiro_settings_path() -> Option<PathBuf> {
-    #[cfg(target_os = "windows")]
-    {
-        std::env::var("APPDATA").ok().map(|appdata| {
-            PathBuf::from(appdata).join("Kiro").join("User").join("settings.json")
-        })
+pub(crate) fn get_kiro_settings_path() -> Option<PathBuf> {
+    crate::kiro_paths::kiro_paths().map(|p| p.settings_file())
+}
+
+fn get_settings_backup_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".kiro-account-manager")
+        .join("backups")
+        .join("kiro-settings")
+}
+
+/// 读取 settings.json 原文（文件不存在时返回 None）
+fn read_settings_text(path: &PathBuf) -> Result<Option<String>, String> {
+    if !path.exists() {
+        return Ok(None);
     }
-    #[cfg(target_os = "macos")]
-    {
-        std::env::var("HOME").ok().map(|home| {
-            PathBuf::from(home)
-                .join("Library")
-                .join("Application Support")
-                .join("Kiro")
-                .join("User")
-                .join("settings.json")
-        })
+    std::fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| format!("读取设置文件失败: {}", e))
+}
+
+/// 备份旧版本，并清理超出数量的旧备份
+fn backup_settings(content: &str) -> Result<(), String> {
+    let dir = get_settings_backup_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+
+    let name = format!("settings-{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S%.3f"));
+    std::fs::write(dir.join(name), content).map_err(|e| format!("备份设置文件失败: {}", e))?;
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    backups.retain(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("settings-")));
+    // 文件名带时间戳，按名称排序即按时间排序
+    backups.sort();
+    let excess = backups.len().saturating_sub(MAX_SETTINGS_BACKUPS);
+    for old in &backups[..excess] {
+        let _ = std::fs::remove_file(old);
     }
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
-    {
-        None
+    Ok(())
+}
+
+/// 本程序最后一次写入内容的哈希（设置监听器据此区分自己的写入和外部修改）
+static LAST_OWN_WRITE: Mutex<Option<String>> = Mutex::new(None);
+
+pub(crate) fn content_hash(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// 内容是否是本程序最后一次写入的版本
+pub(crate) fn is_own_write(content: &str) -> bool {
+    LAST_OWN_WRITE.lock().unwrap().as_deref() == Some(content_hash(content).as_str())
+}
+
+/// 写入临时文件后重命名，避免写到一半时 IDE 读到残缺内容
+fn write_atomic(path: &PathBuf, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建设置目录失败: {}", e))?;
     }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("写入设置文件失败: {}", e))?;
+    *LAST_OWN_WRITE.lock().unwrap() = Some(content_hash(content));
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        format!("写入设置文件失败: {}", e)
+    })
 }
 
-fn get_kiro_settings_inner() -> Result<KiroSettings, String> {
-    let path = get_kiro_settings_path()
-        .ok_or("无法获取 Kiro 设置路径")?;
-    
-    if !path.exists() {
-        return Ok(KiroSettings::default());
+/// 读取整个 settings.json（文件不存在时为空对象）
+pub(crate) fn read_kiro_settings() -> Result<Value, String> {
+    let path = get_kiro_settings_path().ok_or("无法获取 Kiro 设置路径")?;
+    match read_settings_text(&path)? {
+        Some(content) => crate::jsonc::parse(&content).map_err(|e| format!("解析设置文件失败: {}", e)),
+        None => Ok(Value::Object(Default::default())),
+    }
+}
+
+/// 批量修改顶层键（None 表示删除），一次写入、一次备份
+/// 返回是否真的发生了修改
+pub(crate) fn update_kiro_settings(changes: &[(String, Option<Value>)]) -> Result<bool, String> {
+    let path = get_kiro_settings_path().ok_or("无法获取 Kiro 设置路径")?;
+    let original = read_settings_text(&path)?;
+    let source = original.clone().unwrap_or_default();
+
+    // 解析失败时拒绝写入，避免覆盖掉用户的设置
+    crate::jsonc::parse(&source)
+        .map_err(|e| format!("设置文件格式错误，已拒绝写入（请先手动修复 {}）: {}", path.display(), e))?;
+
+    let changes: Vec<(Vec<String>, Option<Value>)> = changes.iter()
+        .map(|(key, value)| (vec![key.clone()], value.clone()))
+        .collect();
+    let mut updated = crate::jsonc::set_many(&source, &changes)?;
+    if original.is_none() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    if original.as_deref() == Some(updated.as_str()) {
+        return Ok(false);
+    }
+
+    // 写入前再校验一次结果
+    crate::jsonc::parse(&updated).map_err(|e| format!("生成的设置文件无效: {}", e))?;
+
+    if let Some(content) = original.as_deref().filter(|c| !c.trim().is_empty()) {
+        backup_settings(content)?;
+    }
+    write_atomic(&path, &updated)?;
+    Ok(true)
+}
+
+/// 读取 settings.json 原文快照，用于失败时回滚
+pub(crate) fn snapshot_kiro_settings() -> Result<Option<String>, String> {
+    let path = get_kiro_settings_path().ok_or("无法获取 Kiro 设置路径")?;
+    read_settings_text(&path)
+}
+
+/// 恢复快照（快照为 None 表示原来没有该文件）
+pub(crate) fn restore_kiro_settings(snapshot: Option<&str>) -> Result<(), String> {
+    let path = get_kiro_settings_path().ok_or("无法获取 Kiro 设置路径")?;
+    match snapshot {
+        Some(content) => write_atomic(&path, content),
+        None if path.exists() => std::fs::remove_file(&path).map_err(|e| format!("删除设置文件失败: {}", e)),
+        None => Ok(()),
     }
-    
-    let content = std::fs::read_to_string(&path)
-        .map_err(|e| format!("读取设置文件失败: {}", e))?;
-    
-    let json: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("解析设置文件失败: {}", e))?;
-    
+}
+
+fn get_kiro_settings_inner() -> Result<KiroSettings, String> {
+    let json = read_kiro_settings()?;
     Ok(KiroSettings {
         http_proxy: json.get("http.proxy").and_then(|v| v.as_str()).map(|s| s.to_string()),
         model_selection: json.get("kiroAgent.modelSelection").and_then(|v| v.as_str()).map(|s| s.to_string()),
@@ -55,78 +155,115 @@ fn get_kiro_settings_inner() -> Result<KiroSettings, String> {
 }
 
 fn set_kiro_proxy_inner(proxy: String) -> Result<(), String> {
-    let path = get_kiro_settings_path()
-        .ok_or("无法获取 Kiro 设置路径")?;
-    
-    let mut settings: serde_json::Value = if path.exists() {
-        let content = std::fs::read_to_string(&path)
-            .map_err(|e| format!("读取设置文件失败: {}", e))?;
-        serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
+    let changes = if proxy.is_empty() {
+        vec![("http.proxy".to_string(), None)]
     } else {
-        serde_json::json!({})
+        vec![
+            ("http.proxy".to_string(), Some(Value::String(proxy))),
+            ("http.proxyStrictSSL".to_string(), Some(Value::Bool(false))),
+            ("http.proxySupport".to_string(), Some(Value::String("on".to_string()))),
+        ]
     };
-    
-    if let Some(obj) = settings.as_object_mut() {
-        if proxy.is_empty() {
-            obj.remove("http.proxy");
-        } else {
-            obj.insert("http.proxy".to_string(), serde_json::Value::String(proxy));
-            obj.insert("http.proxyStrictSSL".to_string(), serde_json::Value::Bool(false));
-            obj.insert("http.proxySupport".to_string(), serde_json::Value::String("on".to_string()));
-        }
-    }
-    
-    let content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("序列化设置失败: {}", e))?;
-    
-    std::fs::write(&path, content)
-        .map_err(|e| format!("写入设置文件失败: {}", e))?;
-    
-    Ok(())
+    update_kiro_settings(&changes).map(|_| ())
 }
 
 fn set_kiro_model_inner(model: String) -> Result<(), String> {
-    let path = get_kiro_settings_path()
-        .ok_or("无法获取 Kiro 设置路径")?;
-    
-    let mut settings: serde_json::Value = if path.exists() {
-        let content = std::fs::read_to_string(&path)
-            .map_err(|e| format!("读取设置文件失败: {}", e))?;
-        serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
-    } else {
-        serde_json::json!({})
-    };
-    
-    if let Some(obj) = settings.as_object_mut() {
-        obj.insert("kiroAgent.modelSelection".to_string(), serde_json::Value::String(model));
-    }
-    
-    let content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("序列化设置失败: {}", e))?;
-    
-    std::fs::write(&path, content)
-        .map_err(|e| format!("写入设置文件失败: {}", e))?;
-    
-    Ok(())
+    update_kiro_settings(&[("kiroAgent.modelSelection".to_string(), Some(Value::String(model)))]).map(|_| ())
 }
 
 #[tauri::command]
-pub async fn get_kiro_settings() -> Result<KiroSettings, String> {
-    tokio::task::spawn_blocking(get_kiro_settings_inner)
+pub async fn get_kiro_settings(installation_id: Option<String>) -> Result<KiroSettings, String> {
+    tokio::task::spawn_blocking(move || with_installation(installation_id.as_deref(), get_kiro_settings_inner))
         .await
         .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub async fn set_kiro_proxy(proxy: String) -> Result<(), String> {
-    tokio::task::spawn_blocking(move || set_kiro_proxy_inner(proxy))
+pub async fn set_kiro_proxy(proxy: String, installation_id: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || with_installation(installation_id.as_deref(), || set_kiro_proxy_inner(proxy)))
         .await
         .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub async fn set_kiro_model(model: String) -> Result<(), String> {
-    tokio::task::spawn_blocking(move || set_kiro_model_inner(model))
+pub async fn set_kiro_model(model: String, installation_id: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || with_installation(installation_id.as_deref(), || set_kiro_model_inner(model)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// 读取任意设置项（键为 settings.json 顶层键，如 "editor.fontSize"）
+/// 以下命令的 installation_id 不传时操作选中的 Kiro 安装
+#[tauri::command]
+pub async fn get_kiro_setting(key: String, installation_id: Option<String>) -> Result<Option<Value>, String> {
+    tokio::task::spawn_blocking(move || with_installation(installation_id.as_deref(), || {
+        read_kiro_settings().map(|mut json| json.as_object_mut().and_then(|obj| obj.remove(&key)))
+    }))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// 读取整个 settings.json
+#[tauri::command]
+pub async fn get_all_kiro_settings(installation_id: Option<String>) -> Result<Value, String> {
+    tokio::task::spawn_blocking(move || with_installation(installation_id.as_deref(), read_kiro_settings))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// 设置任意设置项，value 为 null 时删除该键
+#[tauri::command]
+pub async fn set_kiro_setting(key: String, value: Value, installation_id: Option<String>) -> Result<(), String> {
+    let value = if value.is_null() { None } else { Some(value) };
+    tokio::task::spawn_blocking(move || {
+        with_installation(installation_id.as_deref(), || update_kiro_settings(&[(key, value)]).map(|_| ()))
+    })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn remove_kiro_setting(key: String, installation_id: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        with_installation(installation_id.as_deref(), || update_kiro_settings(&[(key, None)]).map(|_| ()))
+    })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// 列出 settings.json 的历史备份（新的在前）
+#[tauri::command]
+pub fn list_kiro_settings_backups() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(get_settings_backup_dir())
+        .map(|entries| {
+            entries.flatten()
+                .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                .filter(|n| n.starts_with("settings-"))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.reverse();
+    names
+}
+
+/// 用备份恢复 settings.json（当前版本同样会先备份）
+#[tauri::command]
+pub async fn restore_kiro_settings_backup(name: String, installation_id: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || with_installation(installation_id.as_deref(), || {
+        if name.contains(['/', '\\']) || !name.starts_with("settings-") {
+            return Err("无效的备份名称".to_string());
+        }
+        let backup = std::fs::read_to_string(get_settings_backup_dir().join(&name))
+            .map_err(|e| format!("读取备份失败: {}", e))?;
+        crate::jsonc::parse(&backup).map_err(|e| format!("备份文件无效: {}", e))?;
+
+        let path = get_kiro_settings_path().ok_or("无法获取 Kiro 设置路径")?;
+        if let Some(current) = read_settings_text(&path)?.filter(|c| !c.trim().is_empty()) {
+            backup_settings(&current)?;
+        }
+        write_atomic(&path, &backup)
+    }))
         .await
         .map_err(|e| format!("Task failed: {}", e))?
 }
@@ -8,33 +8,26 @@ pub fn get_mcp_config() -> Result<McpConfig, String> {
     McpConfig::load()
 }
 
-/// 保存/更新服务器配置
+/// 保存/更新服务器配置（只修改有变化的字段，文件中其他内容保持不变）
 #[tauri::command]
 pub fn save_mcp_server(name: String, config: McpServer) -> Result<(), String> {
-    let mut mcp_config = McpConfig::load()?;
-    mcp_config.mcp_servers.insert(name, config);
-    mcp_config.save()
+    McpConfig::save_server(&name, &config)
 }
 
 /// 删除服务器
 #[tauri::command]
 pub fn delete_mcp_server(name: String) -> Result<(), String> {
-    let mut mcp_config = McpConfig::load()?;
-    mcp_config.mcp_servers.remove(&name);
-    mcp_config.save()
+    McpConfig::remove_server(&name)
 }
 
 /// 启用/禁用服务器
 #[tauri::command]
 pub fn toggle_mcp_server(name: String, disabled: bool) -> Result<(), String> {
-    let mut mcp_config = McpConfig::load()?;
-    if let Some(server) = mcp_config.mcp_servers.get_mut(&name) {
-        match server {
-            McpServer::Command(cmd) => cmd.disabled = disabled,
-            McpServer::Url(url) => url.disabled = disabled,
-        }
-        mcp_config.save()
-    } else {
-        Err(format!("服务器 {} 不存在", name))
-    }
+    McpConfig::set_server_field(&name, "disabled", Some(serde_json::Value::Bool(disabled)))
+}
+
+/// 校验服务器配置（不保存）
+#[tauri::command]
+pub fn validate_mcp_server(name: String, config: McpServer) -> Result<(), String> {
+    config.validate(&name)
 }
@@ -11,6 +11,7 @@ pub mod mail_cmd;
 pub mod mcp_cmd;
 pub mod powers_cmd;
 pub mod proxy_cmd;
+pub mod settings_profile_cmd;
 pub mod sso_import_cmd;
 pub mod steering_cmd;
 pub mod update_cmd;
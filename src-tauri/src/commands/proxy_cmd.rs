@@ -15,7 +15,7 @@ pub struct SystemProxyInfo {
 // ============================================================
 
 #[cfg(target_os = "windows")]
-fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
+pub(crate) fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
     use winreg::enums::*;
     use winreg::RegKey;
     
@@ -66,7 +66,7 @@ fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
 // ============================================================
 
 #[cfg(target_os = "macos")]
-fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
+pub(crate) fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
     use std::process::Command;
     
     // 获取当前网络服务名称
@@ -132,7 +132,7 @@ fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
 }
 
 #[cfg(not(any(target_os = "windows", target_os = "macos")))]
-fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
+pub(crate) fn detect_system_proxy_inner() -> Result<SystemProxyInfo, String> {
     // Linux: 尝试读取环境变量
     let http_proxy = std::env::var("http_proxy")
         .or_else(|_| std::env::var("HTTP_PROXY"))
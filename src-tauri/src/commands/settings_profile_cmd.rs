@@ -0,0 +1,189 @@
+// Kiro 设置配置命令 (存到 ~/.kiro-account-manager/settings-profiles.json)
+// 配置是 settings.json 中部分键的快照（代理、模型、Agent 设置等），
+// 账号通过 settings_profile_id 引用配置，切换账号时与 Token 一起应用
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+use tauri::State;
+
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsProfile {
+    pub id: String,
+    pub name: String,
+    /// settings.json 顶层键 -> 值，值为 null 表示应用时删除该键
+    pub settings: Map<String, Value>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// 新建 / 更新配置的参数（id 为空时新建）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsProfileInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub settings: Map<String, Value>,
+}
+
+fn get_profiles_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".kiro-account-manager")
+        .join("settings-profiles.json")
+}
+
+pub(crate) fn load_settings_profiles() -> Vec<SettingsProfile> {
+    std::fs::read_to_string(get_profiles_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings_profiles(profiles: &[SettingsProfile]) -> Result<(), String> {
+    let path = get_profiles_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    let content = serde_json::to_string_pretty(profiles)
+        .map_err(|e| format!("序列化配置失败: {}", e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("保存配置失败: {}", e))
+}
+
+pub(crate) fn find_settings_profile(id: &str) -> Option<SettingsProfile> {
+    load_settings_profiles().into_iter().find(|p| p.id == id)
+}
+
+/// 配置转换为 settings.json 修改列表
+/// 开启了模型锁定且配置没有指定模型时，同时写入锁定的模型
+pub(crate) fn profile_changes(profile: &SettingsProfile) -> Vec<(String, Option<Value>)> {
+    let mut changes: Vec<(String, Option<Value>)> = profile.settings.iter()
+        .map(|(key, value)| (key.clone(), if value.is_null() { None } else { Some(value.clone()) }))
+        .collect();
+
+    let app_settings = crate::commands::app_settings_cmd::load_app_settings();
+    if app_settings.lock_model.unwrap_or(false) && !profile.settings.contains_key("kiroAgent.modelSelection") {
+        if let Some(model) = app_settings.locked_model.filter(|m| !m.is_empty()) {
+            changes.push(("kiroAgent.modelSelection".to_string(), Some(Value::String(model))));
+        }
+    }
+    changes
+}
+
+fn now() -> String {
+    chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string()
+}
+
+#[tauri::command]
+pub fn get_settings_profiles() -> Vec<SettingsProfile> {
+    load_settings_profiles()
+}
+
+#[tauri::command]
+pub fn save_settings_profile(profile: SettingsProfileInput) -> Result<SettingsProfile, String> {
+    let name = profile.name.trim().to_string();
+    if name.is_empty() {
+        return Err("配置名称不能为空".to_string());
+    }
+    if profile.settings.keys().any(|k| k.trim().is_empty()) {
+        return Err("设置键不能为空".to_string());
+    }
+
+    let mut profiles = load_settings_profiles();
+    let saved = match profile.id.and_then(|id| profiles.iter_mut().find(|p| p.id == id)) {
+        Some(existing) => {
+            existing.name = name;
+            existing.settings = profile.settings;
+            existing.updated_at = now();
+            existing.clone()
+        }
+        None => {
+            let created = SettingsProfile {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                settings: profile.settings,
+                created_at: now(),
+                updated_at: now(),
+            };
+            profiles.push(created.clone());
+            created
+        }
+    };
+    save_settings_profiles(&profiles)?;
+    Ok(saved)
+}
+
+/// 删除配置，同时清除引用它的账号
+#[tauri::command]
+pub fn delete_settings_profile(state: State<AppState>, id: String) -> Result<bool, String> {
+    let mut profiles = load_settings_profiles();
+    let len_before = profiles.len();
+    profiles.retain(|p| p.id != id);
+    if profiles.len() == len_before {
+        return Ok(false);
+    }
+    save_settings_profiles(&profiles)?;
+
+    let mut store = state.store.lock().unwrap();
+    let mut changed = false;
+    for account in store.accounts.iter_mut().filter(|a| a.settings_profile_id.as_deref() == Some(id.as_str())) {
+        account.settings_profile_id = None;
+        changed = true;
+    }
+    if changed {
+        store.save_to_file();
+    }
+    Ok(true)
+}
+
+/// 从当前 settings.json 中截取指定的键创建配置
+#[tauri::command]
+pub async fn capture_settings_profile(name: String, keys: Vec<String>) -> Result<SettingsProfile, String> {
+    let current = tokio::task::spawn_blocking(crate::commands::kiro_settings_cmd::read_kiro_settings)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    // 当前没有的键记为 null，应用时会删除，保证切换后与截取时一致
+    let settings = keys.into_iter()
+        .map(|key| {
+            let value = current.get(&key).cloned().unwrap_or(Value::Null);
+            (key, value)
+        })
+        .collect();
+    save_settings_profile(SettingsProfileInput { id: None, name, settings })
+}
+
+/// 立即把配置应用到 settings.json
+#[tauri::command]
+pub async fn apply_settings_profile(id: String, installation_id: Option<String>) -> Result<bool, String> {
+    let profile = find_settings_profile(&id).ok_or("配置不存在")?;
+    tokio::task::spawn_blocking(move || {
+        crate::installations::with_installation(installation_id.as_deref(), || {
+            crate::commands::kiro_settings_cmd::update_kiro_settings(&profile_changes(&profile))
+        })
+    })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// 设置账号引用的配置（None 表示不使用配置）
+#[tauri::command]
+pub fn set_account_settings_profile(
+    state: State<AppState>,
+    account_id: String,
+    profile_id: Option<String>,
+) -> Result<(), String> {
+    if let Some(id) = profile_id.as_deref() {
+        find_settings_profile(id).ok_or("配置不存在")?;
+    }
+    let mut store = state.store.lock().unwrap();
+    let account = store.accounts.iter_mut()
+        .find(|a| a.id == account_id)
+        .ok_or("Account not found")?;
+    account.settings_profile_id = profile_id;
+    store.save_to_file();
+    Ok(())
+}
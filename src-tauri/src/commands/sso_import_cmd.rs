@@ -8,7 +8,6 @@ use crate::account::Account;
 use crate::kiro::get_machine_id;
 use crate::codewhisperer_client::CodeWhispererClient;
 
-const PORTAL_BASE: &str = "https://portal.sso.us-east-1.amazonaws.com";
 const START_URL: &str = "https://view.awsapps.com/start";
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,6 +23,8 @@ pub struct SsoImportResult {
 struct RegisterClientResponse {
     client_id: String,
     client_secret: String,
+    #[serde(default)]
+    client_secret_expires_at: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -74,9 +75,11 @@ pub async fn import_from_sso_token(
     state: State<'_, AppState>,
 ) -> Result<SsoImportResult, String> {
     let region = region.unwrap_or_else(|| "us-east-1".to_string());
-    let oidc_base = format!("https://oidc.{}.amazonaws.com", region);
+    let endpoints = crate::endpoints::endpoints();
+    let oidc_base = endpoints.oidc_url(&region);
+    let portal_base = endpoints.sso_portal.as_str();
     
-    let client = reqwest::Client::builder()
+    let client = crate::http_client::client_builder(None)
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
@@ -117,6 +120,7 @@ pub async fn import_from_sso_token(
     
     let client_id = reg_data.client_id;
     let client_secret = reg_data.client_secret;
+    let client_secret_expires_at = reg_data.client_secret_expires_at;
     println!("[SSO Import] 客户端已注册: {}...", &client_id[..20.min(client_id.len())]);
 
     // Step 2: 发起设备授权
@@ -151,7 +155,7 @@ pub async fn import_from_sso_token(
     // Step 3: 验证 Bearer Token
     println!("[SSO Import] Step 3: 验证 Bearer Token...");
     let who_res = client
-        .get(format!("{}/token/whoAmI", PORTAL_BASE))
+        .get(format!("{}/token/whoAmI", portal_base))
         .header("Authorization", format!("Bearer {}", bearer_token))
         .header("Accept", "application/json")
         .send()
@@ -168,7 +172,7 @@ pub async fn import_from_sso_token(
     // Step 4: 获取设备会话令牌
     println!("[SSO Import] Step 4: 获取设备会话令牌...");
     let sess_res = client
-        .post(format!("{}/session/device", PORTAL_BASE))
+        .post(format!("{}/session/device", portal_base))
         .header("Authorization", format!("Bearer {}", bearer_token))
         .header("Content-Type", "application/json")
         .json(&serde_json::json!({}))
@@ -336,6 +340,7 @@ pub async fn import_from_sso_token(
         existing.refresh_token = Some(token_data.refresh_token);
         existing.client_id = Some(client_id);
         existing.client_secret = Some(client_secret);
+        existing.client_secret_expires_at = client_secret_expires_at;
         existing.client_id_hash = Some(client_id_hash);
         existing.region = Some(region);
         existing.expires_at = Some(expires_at.to_rfc3339());
@@ -349,6 +354,7 @@ pub async fn import_from_sso_token(
         account.refresh_token = Some(token_data.refresh_token);
         account.client_id = Some(client_id);
         account.client_secret = Some(client_secret);
+        account.client_secret_expires_at = client_secret_expires_at;
         account.client_id_hash = Some(client_id_hash);
         account.region = Some(region);
         account.expires_at = Some(expires_at.to_rfc3339());
@@ -53,8 +53,8 @@ pub struct WebOAuthInitResponse {
 }
 
 #[tauri::command]
-pub async fn web_oauth_complete(
-    app_handle: tauri::AppHandle,
+pub async fn web_oauth_complete<R: tauri::Runtime>(
+    app_handle: tauri::AppHandle<R>,
     state: State<'_, AppState>,
     callback_url: String,
 ) -> Result<String, String> {
@@ -260,6 +260,7 @@ pub async fn web_oauth_login(
     
     let app_handle_clone = app_handle.clone();
     let window_label_clone = window_label.clone();
+    let redirect_uri = init_result.redirect_uri.clone();
     
     let _window = WebviewWindowBuilder::new(
         &app_handle,
@@ -274,7 +275,7 @@ pub async fn web_oauth_login(
         let url_str = url.as_str();
         println!("[WebView] Navigation: {}", url_str);
         
-        if url_str.starts_with("https://app.kiro.dev/signin/oauth") && url_str.contains("code=") {
+        if url_str.starts_with(&redirect_uri) && url_str.contains("code=") {
             println!("[WebView] Callback URL detected! Emitting event...");
             let _ = app_handle_clone.emit("web-oauth-callback", url_str.to_string());
             
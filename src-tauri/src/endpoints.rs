@@ -0,0 +1,116 @@
+// 服务端点配置
+// 所有出站 API 的基础地址统一从这里解析，便于指向 staging 或本地 mock 服务
+// 优先级: 进程内覆盖（测试用） > 环境变量 > AppSettings.endpoints > 默认值
+
+use crate::commands::app_settings_cmd::load_app_settings;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+const DEFAULT_DESKTOP_AUTH: &str = "https://prod.us-east-1.auth.desktop.kiro.dev";
+const DEFAULT_CODEWHISPERER: &str = "https://codewhisperer.us-east-1.amazonaws.com";
+const DEFAULT_WEB_PORTAL: &str = "https://app.kiro.dev";
+const DEFAULT_WEB_REDIRECT_URI: &str = "https://app.kiro.dev/signin/oauth";
+const DEFAULT_SSO_PORTAL: &str = "https://portal.sso.us-east-1.amazonaws.com";
+const DEFAULT_OIDC: &str = "https://oidc.{region}.amazonaws.com";
+
+/// 已解析的服务端点
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoints {
+    /// Kiro 桌面端认证服务（refreshToken / oauth/token / login）
+    pub desktop_auth: String,
+    /// CodeWhisperer 运行时（getUsageLimits）
+    pub codewhisperer: String,
+    /// KiroWebPortalService（CBOR RPC）
+    pub web_portal: String,
+    /// Web OAuth 回调地址
+    pub web_redirect_uri: String,
+    /// AWS SSO Portal（whoAmI / session/device）
+    pub sso_portal: String,
+    /// AWS SSO OIDC，{region} 会被替换为实际区域
+    pub oidc: String,
+}
+
+/// 设置中的端点覆盖（未填写的字段使用默认值）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointOverrides {
+    pub desktop_auth: Option<String>,
+    pub codewhisperer: Option<String>,
+    pub web_portal: Option<String>,
+    pub web_redirect_uri: Option<String>,
+    pub sso_portal: Option<String>,
+    pub oidc: Option<String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            desktop_auth: DEFAULT_DESKTOP_AUTH.to_string(),
+            codewhisperer: DEFAULT_CODEWHISPERER.to_string(),
+            web_portal: DEFAULT_WEB_PORTAL.to_string(),
+            web_redirect_uri: DEFAULT_WEB_REDIRECT_URI.to_string(),
+            sso_portal: DEFAULT_SSO_PORTAL.to_string(),
+            oidc: DEFAULT_OIDC.to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// 指定区域的 OIDC 地址
+    pub fn oidc_url(&self, region: &str) -> String {
+        self.oidc.replace("{region}", region)
+    }
+
+    fn apply(&mut self, overrides: &EndpointOverrides) {
+        let fields: [(&mut String, &Option<String>); 6] = [
+            (&mut self.desktop_auth, &overrides.desktop_auth),
+            (&mut self.codewhisperer, &overrides.codewhisperer),
+            (&mut self.web_portal, &overrides.web_portal),
+            (&mut self.web_redirect_uri, &overrides.web_redirect_uri),
+            (&mut self.sso_portal, &overrides.sso_portal),
+            (&mut self.oidc, &overrides.oidc),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                *field = value.trim_end_matches('/').to_string();
+            }
+        }
+    }
+}
+
+impl EndpointOverrides {
+    /// 从环境变量读取覆盖（KIRO_AM_DESKTOP_AUTH_URL 等）
+    fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok();
+        Self {
+            desktop_auth: var("KIRO_AM_DESKTOP_AUTH_URL"),
+            codewhisperer: var("KIRO_AM_CODEWHISPERER_URL"),
+            web_portal: var("KIRO_AM_WEB_PORTAL_URL"),
+            web_redirect_uri: var("KIRO_AM_WEB_REDIRECT_URI"),
+            sso_portal: var("KIRO_AM_SSO_PORTAL_URL"),
+            oidc: var("KIRO_AM_OIDC_URL"),
+        }
+    }
+}
+
+static ENDPOINTS_OVERRIDE: RwLock<Option<Endpoints>> = RwLock::new(None);
+
+/// 进程内覆盖所有端点（测试中指向 mock 服务），传 None 恢复正常解析
+#[allow(dead_code)]
+pub fn set_endpoints_override(endpoints: Option<Endpoints>) {
+    *ENDPOINTS_OVERRIDE.write().unwrap() = endpoints;
+}
+
+/// 解析当前生效的服务端点
+pub fn endpoints() -> Endpoints {
+    if let Some(endpoints) = ENDPOINTS_OVERRIDE.read().unwrap().clone() {
+        return endpoints;
+    }
+    let mut endpoints = Endpoints::default();
+    if let Some(overrides) = load_app_settings().endpoints {
+        endpoints.apply(&overrides);
+    }
+    endpoints.apply(&EndpointOverrides::from_env());
+    endpoints
+}
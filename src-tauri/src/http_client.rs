@@ -0,0 +1,309 @@
+// 共享 HTTP 客户端工厂
+// 所有出站请求（Kiro / AWS / 宝塔 / 更新检查）统一从这里创建 reqwest::Client，
+// 根据 AppSettings 配置代理、额外根证书、超时和 User-Agent
+// send_with_retry 负责瞬时错误重试、限流退避和按 host 的并发限制
+
+use crate::commands::app_settings_cmd::{load_app_settings, AppSettings};
+use reqwest::{Certificate, ClientBuilder, Proxy};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 30;
+
+/// 代理来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProxySource {
+    /// 不使用代理（忽略环境变量）
+    Off,
+    /// 用户在设置中填写的代理
+    Custom(String),
+    /// 系统代理（detect_system_proxy）
+    System,
+    /// Kiro IDE settings.json 中的 http.proxy
+    Kiro,
+}
+
+impl ProxySource {
+    fn from_settings(settings: &AppSettings) -> Self {
+        match settings.proxy_mode.as_deref() {
+            Some("off") => ProxySource::Off,
+            Some("custom") => settings.proxy_url.clone()
+                .filter(|u| !u.trim().is_empty())
+                .map(ProxySource::Custom)
+                .unwrap_or(ProxySource::Off),
+            Some("system") => ProxySource::System,
+            _ => ProxySource::Kiro,
+        }
+    }
+
+    /// 解析出实际使用的代理地址（None 表示直连 / 使用 reqwest 默认行为）
+    fn resolve_url(&self) -> Option<String> {
+        match self {
+            ProxySource::Off => None,
+            ProxySource::Custom(url) => Some(url.clone()),
+            ProxySource::System => crate::commands::proxy_cmd::detect_system_proxy_inner()
+                .ok()
+                .filter(|info| info.enabled)
+                .and_then(|info| info.http_proxy),
+            ProxySource::Kiro => get_proxy_from_kiro_settings(),
+        }
+    }
+}
+
+/// 获取 Kiro IDE 设置中的代理
+pub fn get_proxy_from_kiro_settings() -> Option<String> {
+    crate::kiro_paths::kiro_paths().map(|p| p.settings_file()).and_then(|p| {
+        if p.exists() {
+            std::fs::read_to_string(&p).ok()
+        } else {
+            None
+        }
+    })
+    .and_then(|content| {
+        serde_json::from_str::<serde_json::Value>(&content).ok()
+    })
+    .and_then(|json| {
+        json.get("http.proxy")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+    })
+}
+
+/// 读取额外根证书（PEM，可包含多个证书）
+fn load_extra_certificates(paths: &[String]) -> Vec<Certificate> {
+    let mut certs = Vec::new();
+    for path in paths.iter().filter(|p| !p.trim().is_empty()) {
+        let pem = match std::fs::read(path) {
+            Ok(pem) => pem,
+            Err(e) => {
+                println!("[Http] 读取证书失败 {}: {}", path, e);
+                continue;
+            }
+        };
+        match Certificate::from_pem_bundle(&pem) {
+            Ok(bundle) => certs.extend(bundle),
+            Err(e) => println!("[Http] 解析证书失败 {}: {}", path, e),
+        }
+    }
+    certs
+}
+
+/// 按应用设置创建 ClientBuilder
+/// default_user_agent: 客户端自身的默认 UA，用户在设置中配置了 UA 时以设置为准
+/// 调用方可以继续在返回的 builder 上追加 timeout / cookie_store 等配置
+pub fn client_builder(default_user_agent: Option<&str>) -> ClientBuilder {
+    let settings = load_app_settings();
+
+    let connect_timeout = settings.http_connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS);
+    let read_timeout = settings.http_read_timeout_secs.unwrap_or(DEFAULT_READ_TIMEOUT_SECS);
+
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(connect_timeout))
+        .read_timeout(Duration::from_secs(read_timeout));
+
+    let user_agent = settings.http_user_agent.as_deref()
+        .filter(|ua| !ua.trim().is_empty())
+        .or(default_user_agent);
+    if let Some(ua) = user_agent {
+        builder = builder.user_agent(ua);
+    }
+
+    match ProxySource::from_settings(&settings) {
+        ProxySource::Off => {
+            builder = builder.no_proxy();
+        }
+        source => {
+            if let Some(proxy_url) = source.resolve_url() {
+                match Proxy::all(&proxy_url) {
+                    // 本机地址（本地 mock / 调试服务）不走代理
+                    Ok(proxy) => builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string("localhost,127.0.0.1,::1"))),
+                    Err(e) => println!("[Http] 代理配置错误 {}: {}", proxy_url, e),
+                }
+            }
+        }
+    }
+
+    for cert in load_extra_certificates(settings.extra_ca_certs.as_deref().unwrap_or_default()) {
+        builder = builder.add_root_certificate(cert);
+    }
+
+    builder
+}
+
+/// 创建默认配置的 HTTP 客户端
+pub fn build_client() -> Result<reqwest::Client, String> {
+    client_builder(None)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+// ============================================================
+// 重试 / 退避 / 限流
+// ============================================================
+
+const MAX_ATTEMPTS: u32 = 3;
+const BASE_BACKOFF_MS: u64 = 500;
+const MAX_BACKOFF_MS: u64 = 8_000;
+const MAX_RETRY_AFTER_SECS: u64 = 60;
+const MAX_CONCURRENCY_PER_HOST: usize = 4;
+
+/// AWS 限流错误码（x-amzn-ErrorType 响应头）
+const THROTTLING_ERROR_CODES: &[&str] = &[
+    "ThrottlingException",
+    "TooManyRequestsException",
+    "RequestLimitExceeded",
+    "RequestThrottled",
+    "SlowDown",
+    "ServiceUnavailableException",
+];
+
+/// 请求重试策略
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RetryPolicy {
+    /// 幂等请求（查询用量、用户信息等）：连接失败、超时、5xx、429、限流错误都重试
+    Idempotent,
+    /// 非幂等请求（刷新 Token 等，服务端可能已轮换 Token）：
+    /// 只在请求确定未被处理时重试（连接失败、429、限流错误）
+    NonIdempotent,
+}
+
+static HOST_LIMITERS: OnceLock<Mutex<HashMap<String, Arc<Semaphore>>>> = OnceLock::new();
+
+/// 获取指定 host 的并发限制器
+fn host_limiter(host: &str) -> Arc<Semaphore> {
+    let limiters = HOST_LIMITERS.get_or_init(|| Mutex::new(HashMap::new()));
+    limiters.lock().unwrap()
+        .entry(host.to_string())
+        .or_insert_with(|| Arc::new(Semaphore::new(MAX_CONCURRENCY_PER_HOST)))
+        .clone()
+}
+
+/// 响应中的 AWS 限流错误码
+fn throttling_error_code(response: &reqwest::Response) -> Option<String> {
+    let error_type = response.headers()
+        .get("x-amzn-errortype")
+        .and_then(|v| v.to_str().ok())?;
+    // 格式: ThrottlingException:http://internal.amazon.com/...
+    let code = error_type.split(':').next().unwrap_or(error_type);
+    THROTTLING_ERROR_CODES.iter()
+        .any(|c| code.ends_with(c))
+        .then(|| code.to_string())
+}
+
+/// 解析 Retry-After 响应头（秒数或 HTTP 日期）
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())?
+        .trim();
+    let secs = match value.parse::<u64>() {
+        Ok(secs) => secs,
+        Err(_) => {
+            let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (date.timestamp() - chrono::Utc::now().timestamp()).max(0) as u64
+        }
+    };
+    Some(Duration::from_secs(secs.min(MAX_RETRY_AFTER_SECS)))
+}
+
+/// 指数退避 + 抖动: [delay/2, delay)
+fn backoff_delay(attempt: u32) -> Duration {
+    use rand::Rng;
+    let delay = (BASE_BACKOFF_MS << attempt.min(10)).min(MAX_BACKOFF_MS);
+    let jitter = rand::thread_rng().gen_range(0..=delay / 2);
+    Duration::from_millis(delay / 2 + jitter)
+}
+
+/// 判断响应是否需要重试，返回重试原因
+fn retry_reason_for_response(response: &reqwest::Response, policy: RetryPolicy) -> Option<String> {
+    let status = response.status();
+    if let Some(code) = throttling_error_code(response) {
+        return Some(code);
+    }
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Some("429 Too Many Requests".to_string());
+    }
+    if policy == RetryPolicy::Idempotent && status.is_server_error() {
+        return Some(format!("HTTP {}", status.as_u16()));
+    }
+    None
+}
+
+/// 判断网络错误是否需要重试
+fn retry_reason_for_error(error: &reqwest::Error, policy: RetryPolicy) -> Option<String> {
+    if error.is_connect() {
+        return Some(format!("connect error: {}", error));
+    }
+    if policy == RetryPolicy::Idempotent && (error.is_timeout() || error.is_request()) {
+        return Some(format!("request error: {}", error));
+    }
+    None
+}
+
+/// 发送请求：按 host 限制并发，按策略对瞬时错误做指数退避重试
+/// label 用于日志，标识是哪个接口
+pub async fn send_with_retry(
+    request: reqwest::RequestBuilder,
+    policy: RetryPolicy,
+    label: &str,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let host = request.url().host_str().unwrap_or_default().to_string();
+    let limiter = host_limiter(&host);
+
+    let mut pending = Some(request);
+    let mut attempt: u32 = 0;
+    loop {
+        let current = pending.take().expect("request consumed");
+        // body 为流时无法克隆，只能发送一次
+        let next = if attempt + 1 < MAX_ATTEMPTS { current.try_clone() } else { None };
+
+        let result = {
+            let _permit = limiter.acquire().await.expect("host limiter closed");
+            client.execute(current).await
+        };
+
+        let (reason, delay) = match &result {
+            Ok(response) => match retry_reason_for_response(response, policy) {
+                Some(reason) => {
+                    let delay = retry_after(response).unwrap_or_else(|| backoff_delay(attempt));
+                    (reason, delay)
+                }
+                None => return result,
+            },
+            Err(e) => match retry_reason_for_error(e, policy) {
+                Some(reason) => (reason, backoff_delay(attempt)),
+                None => return result,
+            },
+        };
+
+        let Some(next) = next else {
+            println!("[Http] {}", serde_json::json!({
+                "event": "retry_exhausted",
+                "label": label,
+                "host": host,
+                "attempts": attempt + 1,
+                "reason": reason,
+            }));
+            return result;
+        };
+
+        attempt += 1;
+        println!("[Http] {}", serde_json::json!({
+            "event": "retry",
+            "label": label,
+            "host": host,
+            "attempt": attempt,
+            "maxAttempts": MAX_ATTEMPTS,
+            "reason": reason,
+            "delayMs": delay.as_millis() as u64,
+        }));
+        tokio::time::sleep(delay).await;
+        pending = Some(next);
+    }
+}
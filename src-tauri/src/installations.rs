@@ -0,0 +1,306 @@
+// Kiro IDE 安装管理
+// 支持同时存在多个安装（正式版 / 预览版 / 便携版），每个安装有自己的可执行文件、
+// 用户数据目录和 settings.json。自动探测常见安装位置，也可以手动登记；
+// 选中的安装（active_kiro_installation）决定 kiro_paths() 和进程管理操作的目标，
+// 单次操作可以通过 with_installation 临时指定其他安装
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use crate::commands::app_settings_cmd::{load_app_settings, save_app_settings_inner, AppSettings};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroInstallation {
+    pub id: String,
+    pub name: String,
+    /// stable / preview / portable / custom
+    pub channel: String,
+    /// 可执行文件（macOS 为 .app 路径）
+    pub executable: String,
+    /// 用户数据目录覆盖（为空时根据 product.json 推断）
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    /// 自动探测到的安装（不会保存到设置中）
+    #[serde(default)]
+    pub detected: bool,
+}
+
+/// 安装信息（附带解析后的路径）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroInstallationInfo {
+    #[serde(flatten)]
+    pub installation: KiroInstallation,
+    pub exists: bool,
+    pub active: bool,
+    pub data_dir: Option<String>,
+    pub settings_path: Option<String>,
+}
+
+/// 安装包中的 product.json（只关心决定目录名的字段）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductInfo {
+    /// 用户数据目录名（如 "Kiro"）
+    pub name_short: Option<String>,
+    /// 主目录下的配置目录名（如 ".kiro"）
+    pub data_folder_name: Option<String>,
+    /// stable / insider 等
+    pub quality: Option<String>,
+}
+
+impl KiroInstallation {
+    fn executable_path(&self) -> PathBuf {
+        PathBuf::from(&self.executable)
+    }
+
+    /// 安装目录（macOS 为 .app 本身）
+    pub fn install_dir(&self) -> PathBuf {
+        let exe = self.executable_path();
+        if cfg!(target_os = "macos") || exe.is_dir() {
+            return exe;
+        }
+        exe.parent().map(Path::to_path_buf).unwrap_or(exe)
+    }
+
+    pub fn product_info(&self) -> ProductInfo {
+        let app_dir = if cfg!(target_os = "macos") {
+            self.install_dir().join("Contents").join("Resources").join("app")
+        } else {
+            self.install_dir().join("resources").join("app")
+        };
+        std::fs::read_to_string(app_dir.join("product.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 便携版数据目录（安装目录下的 data/user-data）
+    pub fn portable_data_dir(&self) -> Option<PathBuf> {
+        let dir = self.install_dir().join("data").join("user-data");
+        dir.is_dir().then_some(dir)
+    }
+}
+
+// ============================================================
+// 探测
+// ============================================================
+
+/// 平台常见安装位置: (名称, 通道, 可执行文件)
+fn known_locations() -> Vec<(&'static str, &'static str, PathBuf)> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut roots = Vec::new();
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            roots.push(PathBuf::from(local).join("Programs"));
+        }
+        if let Some(program_files) = std::env::var_os("ProgramFiles") {
+            roots.push(PathBuf::from(program_files));
+        }
+        roots.iter()
+            .flat_map(|root| [
+                ("Kiro", "stable", root.join("Kiro").join("Kiro.exe")),
+                ("Kiro Preview", "preview", root.join("Kiro Preview").join("Kiro Preview.exe")),
+            ])
+            .collect()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut roots = vec![PathBuf::from("/Applications")];
+        if let Some(home) = dirs::home_dir() {
+            roots.push(home.join("Applications"));
+        }
+        roots.iter()
+            .flat_map(|root| [
+                ("Kiro", "stable", root.join("Kiro.app")),
+                ("Kiro Preview", "preview", root.join("Kiro Preview.app")),
+            ])
+            .collect()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        crate::process::find_kiro_binary()
+            .map(|bin| vec![("Kiro", "stable", bin)])
+            .unwrap_or_default()
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        Vec::new()
+    }
+}
+
+/// 自动探测到的安装
+pub fn detect_installations() -> Vec<KiroInstallation> {
+    let mut found: Vec<KiroInstallation> = Vec::new();
+    for (name, channel, exe) in known_locations() {
+        if !exe.exists() || found.iter().any(|i| Path::new(&i.executable) == exe) {
+            continue;
+        }
+        let mut installation = KiroInstallation {
+            id: format!("detected-{}", channel),
+            name: name.to_string(),
+            channel: channel.to_string(),
+            executable: exe.to_string_lossy().to_string(),
+            data_dir: None,
+            detected: true,
+        };
+        if installation.portable_data_dir().is_some() {
+            installation.channel = "portable".to_string();
+        }
+        // 同一通道出现多次时（用户目录和系统目录都装了），用序号区分
+        if found.iter().any(|i| i.id == installation.id) {
+            installation.id = format!("{}-{}", installation.id, found.len() + 1);
+        }
+        found.push(installation);
+    }
+    found
+}
+
+/// 所有安装: 手动登记的在前，探测到的（未被登记覆盖的）在后
+pub fn list_installations() -> Vec<KiroInstallation> {
+    let mut all = load_app_settings().kiro_installations.unwrap_or_default();
+    for detected in detect_installations() {
+        if !all.iter().any(|i| i.id == detected.id || i.executable == detected.executable) {
+            all.push(detected);
+        }
+    }
+    all
+}
+
+pub fn find_installation(id: &str) -> Option<KiroInstallation> {
+    list_installations().into_iter().find(|i| i.id == id)
+}
+
+// ============================================================
+// 当前目标安装
+// ============================================================
+
+thread_local! {
+    /// 单次操作临时指定的安装（优先于设置中选中的安装）
+    static SCOPED_INSTALLATION: RefCell<Option<KiroInstallation>> = const { RefCell::new(None) };
+}
+
+struct ScopeGuard(Option<KiroInstallation>);
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        SCOPED_INSTALLATION.with(|s| *s.borrow_mut() = previous);
+    }
+}
+
+/// 当前操作的目标安装，None 表示使用旧的默认探测逻辑
+pub fn current_installation() -> Option<KiroInstallation> {
+    if let Some(scoped) = SCOPED_INSTALLATION.with(|s| s.borrow().clone()) {
+        return Some(scoped);
+    }
+    let settings = load_app_settings();
+    let id = settings.active_kiro_installation.filter(|id| !id.is_empty())?;
+    // 先查登记的安装，避免每次都扫描文件系统
+    settings.kiro_installations.unwrap_or_default()
+        .into_iter()
+        .find(|i| i.id == id)
+        .or_else(|| find_installation(&id))
+}
+
+/// 在指定安装上执行操作（installation_id 为 None 时使用当前选中的安装）
+/// 作用范围是当前线程，调用方应在 spawn_blocking 的闭包内使用
+pub fn with_installation<T>(installation_id: Option<&str>, f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let Some(id) = installation_id.filter(|id| !id.is_empty()) else {
+        return f();
+    };
+    let installation = find_installation(id).ok_or_else(|| format!("Kiro installation not found: {}", id))?;
+    let previous = SCOPED_INSTALLATION.with(|s| s.borrow_mut().replace(installation));
+    let _guard = ScopeGuard(previous);
+    f()
+}
+
+// ============================================================
+// Tauri Commands
+// ============================================================
+
+fn installation_info(installation: KiroInstallation, active_id: Option<&str>) -> KiroInstallationInfo {
+    let paths = crate::kiro_paths::kiro_paths_for(Some(&installation));
+    KiroInstallationInfo {
+        exists: installation.executable_path().exists(),
+        active: active_id == Some(installation.id.as_str()),
+        data_dir: paths.as_ref().map(|p| p.data_dir.to_string_lossy().to_string()),
+        settings_path: paths.as_ref().map(|p| p.settings_file().to_string_lossy().to_string()),
+        installation,
+    }
+}
+
+/// 列出所有 Kiro 安装
+#[tauri::command]
+pub async fn list_kiro_installations() -> Vec<KiroInstallationInfo> {
+    tokio::task::spawn_blocking(|| {
+        let active = load_app_settings().active_kiro_installation;
+        list_installations()
+            .into_iter()
+            .map(|i| installation_info(i, active.as_deref()))
+            .collect()
+    })
+        .await
+        .unwrap_or_default()
+}
+
+/// 登记一个安装（id 已存在时更新）
+#[tauri::command]
+pub fn register_kiro_installation(mut installation: KiroInstallation) -> Result<KiroInstallation, String> {
+    if installation.name.trim().is_empty() {
+        return Err("Installation name is required".to_string());
+    }
+    if !Path::new(&installation.executable).exists() {
+        return Err(format!("Kiro executable not found: {}", installation.executable));
+    }
+    if installation.id.trim().is_empty() {
+        installation.id = uuid::Uuid::new_v4().to_string();
+    }
+    if installation.channel.trim().is_empty() {
+        installation.channel = installation.product_info().quality
+            .map(|q| if q == "stable" { q } else { "preview".to_string() })
+            .unwrap_or_else(|| "custom".to_string());
+    }
+    installation.data_dir = installation.data_dir.filter(|d| !d.trim().is_empty());
+    installation.detected = false;
+
+    let mut installations = load_app_settings().kiro_installations.unwrap_or_default();
+    match installations.iter_mut().find(|i| i.id == installation.id) {
+        Some(existing) => *existing = installation.clone(),
+        None => installations.push(installation.clone()),
+    }
+    save_app_settings_inner(AppSettings { kiro_installations: Some(installations), ..Default::default() })?;
+    Ok(installation)
+}
+
+/// 删除手动登记的安装
+#[tauri::command]
+pub fn remove_kiro_installation(id: String) -> Result<bool, String> {
+    let settings = load_app_settings();
+    let mut installations = settings.kiro_installations.unwrap_or_default();
+    let len_before = installations.len();
+    installations.retain(|i| i.id != id);
+    if installations.len() == len_before {
+        return Ok(false);
+    }
+    save_app_settings_inner(AppSettings { kiro_installations: Some(installations), ..Default::default() })?;
+    if settings.active_kiro_installation.as_deref() == Some(id.as_str()) {
+        set_active_kiro_installation(None)?;
+    }
+    Ok(true)
+}
+
+/// 选择默认操作的安装（None 恢复自动探测）
+#[tauri::command]
+pub fn set_active_kiro_installation(id: Option<String>) -> Result<(), String> {
+    if let Some(id) = id.as_deref() {
+        find_installation(id).ok_or_else(|| format!("Kiro installation not found: {}", id))?;
+    }
+    // 空字符串表示清除（save_app_settings_inner 只合并非 None 字段）
+    save_app_settings_inner(AppSettings {
+        active_kiro_installation: Some(id.unwrap_or_default()),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,536 @@
+// JSONC（带注释 / 尾逗号的 JSON）读取与无损编辑
+// Kiro / VS Code 的 settings.json、mcp.json 允许注释和尾逗号，serde_json 直接解析会失败，
+// 重新序列化又会丢掉用户的注释和格式。这里只改动目标值所在的文本片段，其余内容原样保留
+
+use serde_json::Value;
+
+// ============================================================
+// 语法树（只记录位置）
+// ============================================================
+
+#[derive(Debug)]
+struct Node {
+    start: usize,
+    end: usize,
+    kind: NodeKind,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Object(Vec<Member>),
+    Array(Vec<Node>),
+    Scalar,
+}
+
+#[derive(Debug)]
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+    /// 值后面逗号的位置
+    comma: Option<usize>,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, bytes: text.as_bytes(), pos: 0 }
+    }
+
+    fn error(&self, msg: &str) -> String {
+        let before = &self.text[..self.pos.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map(|l| l.chars().count()).unwrap_or(0) + 1;
+        format!("{} (第 {} 行第 {} 列)", msg, line, column)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// 跳过空白和注释
+    fn skip_trivia(&mut self) -> Result<(), String> {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\r' | b'\n') => self.pos += 1,
+                // UTF-8 BOM
+                Some(0xEF) if self.bytes[self.pos..].starts_with(&[0xEF, 0xBB, 0xBF]) => self.pos += 3,
+                Some(b'/') => match self.bytes.get(self.pos + 1) {
+                    Some(b'/') => {
+                        while !matches!(self.peek(), None | Some(b'\n')) {
+                            self.pos += 1;
+                        }
+                    }
+                    Some(b'*') => {
+                        let close = self.text[self.pos + 2..].find("*/")
+                            .ok_or_else(|| self.error("块注释没有结束"))?;
+                        self.pos += 2 + close + 2;
+                    }
+                    _ => return Err(self.error("无效的字符 '/'")),
+                },
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Node, String> {
+        self.skip_trivia()?;
+        let node = self.parse_value()?;
+        self.skip_trivia()?;
+        if self.pos < self.bytes.len() {
+            return Err(self.error("值后面有多余内容"));
+        }
+        Ok(node)
+    }
+
+    fn parse_value(&mut self) -> Result<Node, String> {
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => {
+                let start = self.pos;
+                self.parse_string()?;
+                Ok(Node { start, end: self.pos, kind: NodeKind::Scalar })
+            }
+            Some(_) => self.parse_literal(),
+            None => Err(self.error("缺少值")),
+        }
+    }
+
+    /// 解析字符串，返回解码后的内容
+    fn parse_string(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => return Err(self.error("字符串没有结束")),
+                Some(b'\\') => self.pos += 2,
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+        serde_json::from_str::<String>(&self.text[start..self.pos])
+            .map_err(|e| self.error(&format!("无效的字符串: {}", e)))
+    }
+
+    fn parse_literal(&mut self) -> Result<Node, String> {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if matches!(b, b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n' | b'/') {
+                break;
+            }
+            self.pos += 1;
+        }
+        let literal = &self.text[start..self.pos];
+        if literal.is_empty() || serde_json::from_str::<Value>(literal).is_err() {
+            self.pos = start;
+            return Err(self.error(&format!("无效的值 '{}'", literal)));
+        }
+        Ok(Node { start, end: self.pos, kind: NodeKind::Scalar })
+    }
+
+    fn parse_object(&mut self) -> Result<Node, String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Node { start, end: self.pos, kind: NodeKind::Object(members) });
+                }
+                Some(b'"') => {}
+                _ => return Err(self.error("对象中应为 '\"' 或 '}'")),
+            }
+
+            let key_start = self.pos;
+            let key = self.parse_string()?;
+            self.skip_trivia()?;
+            if self.peek() != Some(b':') {
+                return Err(self.error("键后面缺少 ':'"));
+            }
+            self.pos += 1;
+            self.skip_trivia()?;
+            let value = self.parse_value()?;
+            self.skip_trivia()?;
+
+            let comma = match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                    Some(self.pos - 1)
+                }
+                Some(b'}') => None,
+                _ => return Err(self.error("对象成员之间缺少 ','")),
+            };
+            members.push(Member { key, key_start, value, comma });
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Node, String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                return Ok(Node { start, end: self.pos, kind: NodeKind::Array(items) });
+            }
+            items.push(self.parse_value()?);
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {}
+                _ => return Err(self.error("数组元素之间缺少 ','")),
+            }
+        }
+    }
+}
+
+// ============================================================
+// 读取
+// ============================================================
+
+/// 转换为标准 JSON：注释替换为空格，去掉尾逗号
+fn to_strict_json(text: &str, root: &Node) -> String {
+    let mut out = text.as_bytes().to_vec();
+    blank_comments(text, &mut out);
+    remove_trailing_commas(root, &mut out);
+    String::from_utf8(out).unwrap_or_default()
+}
+
+fn blank_comments(text: &str, out: &mut [u8]) {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    let mut in_string = false;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if in_string {
+            match b {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        match (b, bytes.get(i + 1)) {
+            (b'"', _) => {
+                in_string = true;
+                i += 1;
+            }
+            (b'/', Some(b'/')) => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    out[i] = b' ';
+                    i += 1;
+                }
+            }
+            (b'/', Some(b'*')) => {
+                let end = text[i + 2..].find("*/").map(|p| i + 2 + p + 2).unwrap_or(bytes.len());
+                for byte in &mut out[i..end] {
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    // BOM
+    if out.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        out[..3].copy_from_slice(b"   ");
+    }
+}
+
+fn remove_trailing_commas(node: &Node, out: &mut [u8]) {
+    match &node.kind {
+        NodeKind::Object(members) => {
+            if let Some(comma) = members.last().and_then(|m| m.comma) {
+                out[comma] = b' ';
+            }
+            for m in members {
+                remove_trailing_commas(&m.value, out);
+            }
+        }
+        NodeKind::Array(items) => {
+            if let Some(last) = items.last() {
+                // 最后一个元素之后到 ']' 之间的逗号
+                for byte in &mut out[last.end..node.end - 1] {
+                    if *byte == b',' {
+                        *byte = b' ';
+                    }
+                }
+            }
+            for item in items {
+                remove_trailing_commas(item, out);
+            }
+        }
+        NodeKind::Scalar => {}
+    }
+}
+
+/// 解析 JSONC 文本为 Value（空文本视为空对象）
+pub fn parse(text: &str) -> Result<Value, String> {
+    if text.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    let root = Parser::new(text).parse_document()?;
+    serde_json::from_str(&to_strict_json(text, &root)).map_err(|e| format!("解析失败: {}", e))
+}
+
+/// 按路径读取值（路径每一段是对象的键，键本身可以包含 '.'）
+pub fn get(text: &str, path: &[&str]) -> Result<Option<Value>, String> {
+    let mut value = parse(text)?;
+    for key in path {
+        match value {
+            Value::Object(mut map) => match map.remove(*key) {
+                Some(v) => value = v,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+// ============================================================
+// 编辑
+// ============================================================
+
+/// 文本格式信息：缩进单位和换行符
+struct Style {
+    indent_unit: String,
+    newline: &'static str,
+}
+
+impl Style {
+    fn detect(text: &str) -> Self {
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let indent_unit = text.lines()
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .find(|indent| !indent.is_empty())
+            .map(|indent| if indent.starts_with('\t') { "\t".to_string() } else { indent.to_string() })
+            .unwrap_or_else(|| "    ".to_string());
+        Self { indent_unit, newline }
+    }
+
+    /// 格式化值，多行时每行加上 base_indent
+    fn format_value(&self, value: &Value, base_indent: &str) -> String {
+        use serde::Serialize;
+        let mut buf = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent_unit.as_bytes());
+        let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        value.serialize(&mut ser).expect("serialize json value");
+        let pretty = String::from_utf8(buf).unwrap_or_default();
+        pretty.replace('\n', &format!("{}{}", self.newline, base_indent))
+    }
+}
+
+/// 行首位置
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map(|p| p + 1).unwrap_or(0)
+}
+
+/// 所在行的缩进
+fn line_indent(text: &str, pos: usize) -> &str {
+    let start = line_start(text, pos);
+    let line = &text[start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// pos 之后同一行只剩空白 / 行注释时，返回行尾（换行符之前）的位置
+fn end_of_line_trivia(text: &str, pos: usize) -> Option<usize> {
+    let rest = &text[pos..];
+    let trimmed = rest.trim_start_matches([' ', '\t']);
+    let offset = rest.len() - trimmed.len();
+    if trimmed.starts_with("//") {
+        return Some(pos + offset + trimmed.find('\n').unwrap_or(trimmed.len()));
+    }
+    if trimmed.is_empty() || trimmed.starts_with('\n') || trimmed.starts_with("\r\n") {
+        return Some(pos + offset);
+    }
+    None
+}
+
+/// 去掉行尾的 '\r'（\r\n 换行时插入位置应在 \r 之前）
+fn before_cr(text: &str, pos: usize) -> usize {
+    if pos > 0 && text.as_bytes()[pos - 1] == b'\r' { pos - 1 } else { pos }
+}
+
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+fn apply_edits(text: &str, edits: Vec<Edit>) -> String {
+    // 从后往前应用；同一位置的多个插入保持原有先后顺序
+    let mut edits: Vec<(usize, Edit)> = edits.into_iter().enumerate().collect();
+    edits.sort_by_key(|(index, e)| std::cmp::Reverse((e.start, *index)));
+    let mut out = text.to_string();
+    for (_, edit) in edits {
+        out.replace_range(edit.start..edit.end, &edit.text);
+    }
+    out
+}
+
+/// 由剩余路径构造嵌套对象: ["a", "b"], v -> {"b": v}（第一段作为成员键）
+fn nest(path: &[&str], value: Value) -> Value {
+    path.iter().rev().fold(value, |acc, key| {
+        let mut map = serde_json::Map::new();
+        map.insert(key.to_string(), acc);
+        Value::Object(map)
+    })
+}
+
+/// 在对象末尾插入成员
+fn insert_member(text: &str, style: &Style, object: &Node, key: &str, value: &Value) -> Vec<Edit> {
+    let NodeKind::Object(members) = &object.kind else { unreachable!() };
+    let key_json = serde_json::to_string(key).unwrap_or_default();
+
+    match members.last() {
+        Some(last) => {
+            let indent = line_indent(text, last.key_start).to_string();
+            let same_line = line_start(text, last.key_start) <= object.start;
+            let member = format!("{}: {}", key_json, style.format_value(value, &indent));
+
+            if same_line {
+                // 单行对象 {"a": 1} -> {"a": 1, "b": 2}
+                let (pos, prefix) = match last.comma {
+                    Some(comma) => (comma + 1, " "),
+                    None => (last.value.end, ", "),
+                };
+                return vec![Edit { start: pos, end: pos, text: format!("{}{}", prefix, member) }];
+            }
+
+            let mut edits = Vec::new();
+            let anchor = match last.comma {
+                Some(comma) => comma + 1,
+                None => {
+                    edits.push(Edit { start: last.value.end, end: last.value.end, text: ",".to_string() });
+                    last.value.end
+                }
+            };
+            // 原来有尾逗号时，新成员也保留尾逗号
+            let trailing = if last.comma.is_some() { "," } else { "" };
+            let new_line = format!("{}{}{}{}", style.newline, indent, member, trailing);
+            match end_of_line_trivia(text, anchor) {
+                Some(eol) => {
+                    let pos = before_cr(text, eol);
+                    edits.push(Edit { start: pos, end: pos, text: new_line });
+                }
+                None => edits.push(Edit { start: anchor, end: anchor, text: new_line }),
+            }
+            edits
+        }
+        None => {
+            let parent_indent = line_indent(text, object.start).to_string();
+            let indent = format!("{}{}", parent_indent, style.indent_unit);
+            let member = format!("{}: {}", key_json, style.format_value(value, &indent));
+            let inner = &text[object.start + 1..object.end - 1];
+            let text = if inner.trim().is_empty() {
+                // {} -> { "a": 1 }，替换原有空白
+                return vec![Edit {
+                    start: object.start + 1,
+                    end: object.end - 1,
+                    text: format!("{}{}{}{}{}", style.newline, indent, member, style.newline, parent_indent),
+                }];
+            } else {
+                // 只有注释的对象：插在 '{' 后面
+                format!("{}{}{}", style.newline, indent, member)
+            };
+            vec![Edit { start: object.start + 1, end: object.start + 1, text }]
+        }
+    }
+}
+
+/// 删除成员（连同它的逗号、同一行的注释）
+fn remove_member(text: &str, members: &[Member], index: usize) -> Vec<Edit> {
+    let member = &members[index];
+    let ls = line_start(text, member.key_start);
+    let owns_line = text[ls..member.key_start].trim().is_empty();
+    let start = if owns_line { ls } else { member.key_start };
+
+    let mut end = member.comma.map(|c| c + 1).unwrap_or(member.value.end);
+    let mut edits = Vec::new();
+    if member.comma.is_none() && index > 0 {
+        // 删除最后一个成员时，前一个成员的逗号也要去掉
+        if let Some(prev_comma) = members[index - 1].comma {
+            edits.push(Edit { start: prev_comma, end: prev_comma + 1, text: String::new() });
+        }
+    }
+
+    match end_of_line_trivia(text, end) {
+        Some(eol) if owns_line => {
+            // 整行删除（包括换行符）
+            end = eol;
+            if text[end..].starts_with("\r\n") {
+                end += 2;
+            } else if text[end..].starts_with('\n') {
+                end += 1;
+            }
+        }
+        _ => {
+            end += text[end..].len() - text[end..].trim_start_matches([' ', '\t']).len();
+        }
+    }
+    edits.push(Edit { start, end, text: String::new() });
+    edits
+}
+
+/// 按路径设置值，中间缺失的对象会自动创建；value 为 None 时删除该键
+pub fn set(text: &str, path: &[&str], value: Option<&Value>) -> Result<String, String> {
+    if path.is_empty() {
+        return Err("路径不能为空".to_string());
+    }
+    let source = if text.trim().is_empty() { "{}" } else { text };
+    let root = Parser::new(source).parse_document()?;
+    let style = Style::detect(source);
+
+    let mut node = &root;
+    for (depth, key) in path.iter().enumerate() {
+        let NodeKind::Object(members) = &node.kind else {
+            return Err(format!("'{}' 不是对象", path[..depth].join(" > ")));
+        };
+        let is_last = depth == path.len() - 1;
+        let found = members.iter().rposition(|m| m.key == *key);
+
+        match (found, value) {
+            (Some(index), _) if !is_last => node = &members[index].value,
+            (Some(index), Some(value)) => {
+                let target = &members[index].value;
+                let indent = line_indent(source, members[index].key_start).to_string();
+                let edit = Edit { start: target.start, end: target.end, text: style.format_value(value, &indent) };
+                return Ok(apply_edits(source, vec![edit]));
+            }
+            (Some(index), None) => return Ok(apply_edits(source, remove_member(source, members, index))),
+            (None, None) => return Ok(text.to_string()),
+            (None, Some(value)) => {
+                let nested = nest(&path[depth + 1..], value.clone());
+                let edits = insert_member(source, &style, node, key, &nested);
+                return Ok(apply_edits(source, edits));
+            }
+        }
+    }
+    unreachable!()
+}
+
+/// 批量修改（按顺序依次应用，任何一步失败则整体失败）
+pub fn set_many(text: &str, changes: &[(Vec<String>, Option<Value>)]) -> Result<String, String> {
+    let mut current = text.to_string();
+    for (path, value) in changes {
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        current = set(&current, &path, value.as_ref())?;
+    }
+    Ok(current)
+}
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use rusqlite::{Connection, OpenFlags};
+use crate::kiro_paths::kiro_paths;
 
 // ===== Kiro IDE 本地 Token =====
 
@@ -31,34 +32,116 @@ pub struct ClientRegistration {
 
 #[tauri::command]
 pub fn get_kiro_local_token() -> Option<KiroLocalToken> {
-    let home = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .ok()?;
-    let path = std::path::Path::new(&home)
-        .join(".aws")
-        .join("sso")
-        .join("cache")
-        .join("kiro-auth-token.json");
+    let path = kiro_paths()?.auth_token_file();
     
     let content = std::fs::read_to_string(&path).ok()?;
     serde_json::from_str(&content).ok()
 }
 
+impl ClientRegistration {
+    /// expiresAt 转换为 Unix 秒
+    pub fn expires_at_timestamp(&self) -> Option<i64> {
+        self.expires_at.as_deref()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.timestamp())
+    }
+}
+
 /// 读取 IdC 客户端注册信息
 pub fn get_client_registration(client_id_hash: &str) -> Option<ClientRegistration> {
-    let home = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .ok()?;
-    let path = std::path::Path::new(&home)
-        .join(".aws")
-        .join("sso")
-        .join("cache")
-        .join(format!("{}.json", client_id_hash));
+    let path = kiro_paths()?.client_registration_file(client_id_hash);
     
     let content = std::fs::read_to_string(&path).ok()?;
     serde_json::from_str(&content).ok()
 }
 
+/// 获取 SSO 缓存目录 (~/.aws/sso/cache)
+fn get_sso_cache_dir() -> Result<std::path::PathBuf, String> {
+    kiro_paths()
+        .map(|p| p.sso_cache_dir)
+        .ok_or("Cannot find home directory".to_string())
+}
+
+/// 写入 IdC 客户端注册文件 {clientIdHash}.json（原子写入）
+/// expires_at 为 RegisterClient 返回的 clientSecretExpiresAt (Unix 秒)，缺失时按 90 天估算
+pub fn write_client_registration(
+    client_id_hash: &str,
+    client_id: &str,
+    client_secret: &str,
+    expires_at: Option<i64>,
+) -> Result<(), String> {
+    let dir_path = get_sso_cache_dir()?;
+    std::fs::create_dir_all(&dir_path)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let client_expires = expires_at
+        .and_then(|ts| chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0))
+        .unwrap_or_else(|| chrono::Utc::now() + chrono::Duration::days(90));
+    let client_reg_data = serde_json::json!({
+        "clientId": client_id,
+        "clientSecret": client_secret,
+        "expiresAt": client_expires.to_rfc3339()
+    });
+    let client_reg_content = serde_json::to_string_pretty(&client_reg_data)
+        .map_err(|e| format!("Failed to serialize client registration: {}", e))?;
+
+    // 原子写入
+    let client_reg_path = dir_path.join(format!("{}.json", client_id_hash));
+    let client_reg_temp_path = dir_path.join(format!("{}.json.tmp", client_id_hash));
+    std::fs::write(&client_reg_temp_path, client_reg_content)
+        .map_err(|e| format!("Failed to write client registration temp: {}", e))?;
+    std::fs::rename(&client_reg_temp_path, &client_reg_path)
+        .map_err(|e| format!("Failed to rename client registration: {}", e))?;
+    Ok(())
+}
+
+/// IdC 账号重新注册客户端后，如果 IDE 当前正在使用该账号，同步更新 IDE 侧的
+/// kiro-auth-token.json 和 {clientIdHash}.json
+/// 返回 true 表示 IDE 文件已更新
+pub fn sync_idc_credentials_to_ide(
+    old_refresh_token: &str,
+    client_id_hash: &str,
+    client_id: &str,
+    client_secret: &str,
+    client_secret_expires_at: Option<i64>,
+    access_token: &str,
+    refresh_token: &str,
+) -> Result<bool, String> {
+    let local = match get_kiro_local_token() {
+        Some(t) => t,
+        None => return Ok(false),
+    };
+    if local.refresh_token.as_deref() != Some(old_refresh_token)
+        || local.client_id_hash.as_deref() != Some(client_id_hash)
+    {
+        return Ok(false);
+    }
+
+    write_client_registration(client_id_hash, client_id, client_secret, client_secret_expires_at)?;
+
+    let file_path = get_sso_cache_dir()?.join("kiro-auth-token.json");
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read token file: {}", e))?;
+    let mut token_data: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse token file: {}", e))?;
+    if let Some(obj) = token_data.as_object_mut() {
+        let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+        obj.insert("accessToken".to_string(), serde_json::json!(access_token));
+        obj.insert("refreshToken".to_string(), serde_json::json!(refresh_token));
+        obj.insert("expiresAt".to_string(), serde_json::json!(expires_at.to_rfc3339()));
+    }
+    let content = serde_json::to_string_pretty(&token_data)
+        .map_err(|e| format!("Failed to serialize: {}", e))?;
+    let temp_file_path = file_path.with_extension("json.tmp");
+    std::fs::write(&temp_file_path, &content)
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+    std::fs::rename(&temp_file_path, &file_path)
+        .map_err(|e| format!("Failed to rename file: {}", e))?;
+
+    println!("[IdC] IDE credentials updated for clientIdHash {}", client_id_hash);
+    Ok(true)
+}
+
 // ===== Kiro IDE 遥测信息 =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,35 +153,11 @@ pub struct KiroTelemetryInfo {
     pub service_machine_id: Option<String>,
 }
 
-/// 获取 Kiro 数据目录
-fn get_kiro_data_dir() -> Option<std::path::PathBuf> {
-    #[cfg(target_os = "windows")]
-    {
-        std::env::var("APPDATA").ok().map(|p| std::path::PathBuf::from(p).join("Kiro"))
-    }
-    #[cfg(target_os = "macos")]
-    {
-        std::env::var("HOME").ok().map(|p| {
-            std::path::PathBuf::from(p)
-                .join("Library")
-                .join("Application Support")
-                .join("Kiro")
-        })
-    }
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
-    {
-        None
-    }
-}
-
 fn get_kiro_telemetry_info_inner() -> Option<KiroTelemetryInfo> {
-    let kiro_dir = get_kiro_data_dir()?;
+    let paths = kiro_paths()?;
     
     // 从 storage.json 读取
-    let storage_path = kiro_dir
-        .join("User")
-        .join("globalStorage")
-        .join("storage.json");
+    let storage_path = paths.storage_file();
     
     let content = std::fs::read_to_string(&storage_path).ok()?;
     let json: serde_json::Value = serde_json::from_str(&content).ok()?;
@@ -111,10 +170,7 @@ fn get_kiro_telemetry_info_inner() -> Option<KiroTelemetryInfo> {
     };
     
     // 从 state.vscdb 读取 serviceMachineId
-    let db_path = kiro_dir
-        .join("User")
-        .join("globalStorage")
-        .join("state.vscdb");
+    let db_path = paths.state_db_file();
     
     if db_path.exists() {
         // 只读模式打开，避免被 Kiro IDE 占用时出错
@@ -140,6 +196,72 @@ pub async fn get_kiro_telemetry_info() -> Option<KiroTelemetryInfo> {
         .flatten()
 }
 
+// ===== 最近打开的工作区 =====
+
+/// 最近打开的工作区 / 文件（来自 state.vscdb 的 history.recentlyOpenedPathsList）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentWorkspace {
+    pub path: String,
+    /// folder / workspace / file
+    pub kind: String,
+}
+
+/// 读取最近打开列表（按最近使用排序，忽略远程工作区）
+pub fn recent_kiro_workspaces() -> Vec<RecentWorkspace> {
+    let Some(db_path) = kiro_paths().map(|p| p.state_db_file()).filter(|p| p.exists()) else {
+        return Vec::new();
+    };
+    let Ok(conn) = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return Vec::new();
+    };
+    let Ok(raw) = conn.query_row(
+        "SELECT value FROM ItemTable WHERE key = 'history.recentlyOpenedPathsList'",
+        [],
+        |row| row.get::<_, String>(0)
+    ) else {
+        return Vec::new();
+    };
+    let json: serde_json::Value = serde_json::from_str(&raw).unwrap_or_default();
+
+    json.get("entries")
+        .and_then(|v| v.as_array())
+        .map(|entries| {
+            entries.iter()
+                .filter_map(|entry| {
+                    let (uri, kind) = if let Some(uri) = entry.get("folderUri").and_then(|v| v.as_str()) {
+                        (uri, "folder")
+                    } else if let Some(uri) = entry.pointer("/workspace/configPath").and_then(|v| v.as_str()) {
+                        (uri, "workspace")
+                    } else {
+                        (entry.get("fileUri").and_then(|v| v.as_str())?, "file")
+                    };
+                    let path = url::Url::parse(uri).ok()
+                        .filter(|u| u.scheme() == "file")?
+                        .to_file_path().ok()?;
+                    Some(RecentWorkspace { path: path.to_string_lossy().to_string(), kind: kind.to_string() })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 最近使用且仍然存在的工作区（文件夹或 .code-workspace）
+pub fn last_kiro_workspace() -> Option<String> {
+    recent_kiro_workspaces()
+        .into_iter()
+        .filter(|w| w.kind != "file")
+        .find(|w| std::path::Path::new(&w.path).exists())
+        .map(|w| w.path)
+}
+
+#[tauri::command]
+pub async fn get_kiro_recent_workspaces() -> Vec<RecentWorkspace> {
+    tokio::task::spawn_blocking(recent_kiro_workspaces)
+        .await
+        .unwrap_or_default()
+}
+
 /// 获取当前机器 ID（供其他模块使用）
 pub fn get_machine_id() -> String {
     get_kiro_telemetry_info_inner()
@@ -157,7 +279,9 @@ pub fn get_machine_id() -> String {
 
 // ===== 切换账号 =====
 
-use crate::process::{check_kiro_running, kill_kiro, launch_kiro};
+use crate::commands::kiro_settings_cmd::{restore_kiro_settings, snapshot_kiro_settings, update_kiro_settings};
+use crate::commands::settings_profile_cmd::{find_settings_profile, profile_changes};
+use crate::process::{check_kiro_running, launch_kiro, shutdown_kiro, shutdown_timeout, LaunchOptions, ShutdownResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -166,6 +290,12 @@ pub struct SwitchAccountResult {
     pub message: String,
     pub kiro_was_running: bool,
     pub kiro_restarted: bool,
+    /// 关闭 IDE 的结果（需要重置机器 ID 时才会关闭）
+    pub shutdown: Option<ShutdownResult>,
+    /// 已应用的设置配置名称
+    pub settings_profile: Option<String>,
+    /// 重启后重新打开的工作区
+    pub reopened_workspace: Option<String>,
 }
 
 /// 切换账号参数
@@ -189,60 +319,92 @@ pub struct SwitchAccountParams {
     pub client_secret: Option<String>,
     #[serde(default)]
     pub region: Option<String>,
+    #[serde(default)]
+    pub client_secret_expires_at: Option<i64>,
     // 选项
     #[serde(default)]
     pub reset_machine_id: Option<bool>,
     #[serde(default)]
     pub auto_restart: Option<bool>,
+    /// 同时应用的 Kiro 设置配置（账号的 settingsProfileId）
+    #[serde(default)]
+    pub settings_profile_id: Option<String>,
+    /// 重启 IDE 时重新打开关闭前的工作区（默认开启）
+    #[serde(default)]
+    pub reopen_workspace: Option<bool>,
+    /// 目标 Kiro 安装（不传时使用选中的安装）
+    #[serde(default)]
+    pub installation_id: Option<String>,
 }
 
-/// 切换 Kiro 账号（直接写入 Token 文件，仅重置机器ID时才关闭IDE）
-#[tauri::command]
-pub async fn switch_kiro_account(params: SwitchAccountParams) -> Result<SwitchAccountResult, String> {
-    // 使用 spawn_blocking 避免阻塞异步运行时
-    tokio::task::spawn_blocking(move || {
-        let kiro_was_running = check_kiro_running();
-        let should_reset = params.reset_machine_id.unwrap_or(false);
-        let should_restart = params.auto_restart.unwrap_or(true);
-        let auth_method = params.auth_method.unwrap_or_else(|| "social".to_string());
-        let access_token = params.access_token;
-        let refresh_token = params.refresh_token;
-        let provider = params.provider;
-        let profile_arn = params.profile_arn;
-        let client_id_hash = params.client_id_hash;
-        let client_id = params.client_id;
-        let client_secret = params.client_secret;
-        let region = params.region;
-        
-        // 1. 只在需要重置机器 ID 时才关闭 IDE
-        if should_reset && kiro_was_running {
-            kill_kiro()?;
-            // 等待进程退出
-            std::thread::sleep(std::time::Duration::from_millis(300));
+fn switch_kiro_account_inner(params: SwitchAccountParams) -> Result<SwitchAccountResult, String> {
+    let kiro_was_running = check_kiro_running();
+    let should_reset = params.reset_machine_id.unwrap_or(false);
+    let should_restart = params.auto_restart.unwrap_or(true);
+    let auth_method = params.auth_method.unwrap_or_else(|| "social".to_string());
+    let access_token = params.access_token;
+    let refresh_token = params.refresh_token;
+    let provider = params.provider;
+    let profile_arn = params.profile_arn;
+    let client_id_hash = params.client_id_hash;
+    let client_id = params.client_id;
+    let client_secret = params.client_secret;
+    let region = params.region;
+    let client_secret_expires_at = params.client_secret_expires_at;
+    let settings_profile_id = params.settings_profile_id;
+    
+    // 记录关闭前打开的工作区，重启后重新打开
+    let last_workspace = if kiro_was_running && should_restart && params.reopen_workspace.unwrap_or(true) {
+        last_kiro_workspace()
+    } else {
+        None
+    };
+    
+    // 1. 只在需要重置机器 ID 时才关闭 IDE（等进程树真正退出后再改文件）
+    let shutdown = if should_reset && kiro_was_running {
+        let result = shutdown_kiro(shutdown_timeout());
+        if !result.is_stopped() {
+            return Err(format!("Failed to close Kiro IDE: {}", result.message));
+        }
+        Some(result)
+    } else {
+        None
+    };
+    
+    // 2. 如果需要重置机器 ID
+    if should_reset {
+        let _ = reset_kiro_machine_id_inner();
+    }
+    
+    // 3. 应用账号的设置配置（先记录 settings.json 原文，Token 写入失败时回滚）
+    let settings_profile = settings_profile_id.as_deref().and_then(|id| {
+        let profile = find_settings_profile(id);
+        if profile.is_none() {
+            println!("[Switch] Settings profile {} not found, skipped", id);
         }
-        
-        // 2. 如果需要重置机器 ID
-        if should_reset {
-            let _ = reset_kiro_machine_id_inner();
+        profile
+    });
+    let settings_snapshot = match &settings_profile {
+        Some(profile) => {
+            let snapshot = snapshot_kiro_settings()?;
+            update_kiro_settings(&profile_changes(profile))
+                .map_err(|e| format!("Failed to apply settings profile: {}", e))?;
+            Some(snapshot)
         }
-        
-        // 3. 替换 Token
-        let home = std::env::var("USERPROFILE")
-            .or_else(|_| std::env::var("HOME"))
-            .map_err(|_| "Cannot find home directory")?;
-        
-        let dir_path = std::path::Path::new(&home)
-            .join(".aws")
-            .join("sso")
-            .join("cache");
-        
+        None => None,
+    };
+    
+    // 4. 替换 Token
+    let dir_path = get_sso_cache_dir()?;
+    let file_path = dir_path.join("kiro-auth-token.json");
+    let previous_token = std::fs::read_to_string(&file_path).ok();
+    
+    let write_token = || -> Result<(), String> {
         std::fs::create_dir_all(&dir_path)
             .map_err(|e| format!("Failed to create directory: {}", e))?;
-        
-        let file_path = dir_path.join("kiro-auth-token.json");
-        
+    
         let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
-        
+    
         // 根据 auth_method 构建不同的 token 数据
         let token_data = if auth_method == "IdC" {
             // IdC 账号: clientIdHash + region
@@ -271,51 +433,65 @@ pub async fn switch_kiro_account(params: SwitchAccountParams) -> Result<SwitchAc
                 "provider": provider
             })
         };
-        
+    
         let content = serde_json::to_string_pretty(&token_data)
             .map_err(|e| format!("Failed to serialize: {}", e))?;
-        
+    
         // 原子写入：先写临时文件，再覆盖
         let temp_file_path = dir_path.join("kiro-auth-token.json.tmp");
         std::fs::write(&temp_file_path, &content)
             .map_err(|e| format!("Failed to write temp file: {}", e))?;
         std::fs::rename(&temp_file_path, &file_path)
             .map_err(|e| format!("Failed to rename file: {}", e))?;
-        
+    
         // IdC 账号还需要写入 Client Registration 文件
         if auth_method == "IdC" {
             if let (Some(hash), Some(cid), Some(csec)) = (client_id_hash, client_id, client_secret) {
-                let client_reg_path = dir_path.join(format!("{}.json", hash));
-                let client_reg_temp_path = dir_path.join(format!("{}.json.tmp", hash));
-                let client_expires = chrono::Utc::now() + chrono::Duration::days(90);
-                let client_reg_data = serde_json::json!({
-                    "clientId": cid,
-                    "clientSecret": csec,
-                    "expiresAt": client_expires.to_rfc3339()
-                });
-                let client_reg_content = serde_json::to_string_pretty(&client_reg_data)
-                    .map_err(|e| format!("Failed to serialize client registration: {}", e))?;
-                // 原子写入
-                std::fs::write(&client_reg_temp_path, client_reg_content)
-                    .map_err(|e| format!("Failed to write client registration temp: {}", e))?;
-                std::fs::rename(&client_reg_temp_path, &client_reg_path)
-                    .map_err(|e| format!("Failed to rename client registration: {}", e))?;
+                write_client_registration(&hash, &cid, &csec, client_secret_expires_at)?;
             }
         }
-        
-        // 4. 切换完成
-        let kiro_restarted = if kiro_was_running && should_restart {
-            launch_kiro().is_ok()
-        } else {
-            false
-        };
-        
-        Ok(SwitchAccountResult {
-            success: true,
-            message: format!("Switched to {} ({}) account", provider, auth_method),
-            kiro_was_running,
-            kiro_restarted,
-        })
+        Ok(())
+    };
+    
+    if let Err(e) = write_token() {
+        // 回滚：恢复旧 Token 和 settings.json
+        match &previous_token {
+            Some(previous) => { let _ = std::fs::write(&file_path, previous); }
+            None => { let _ = std::fs::remove_file(&file_path); }
+        }
+        if let Some(snapshot) = &settings_snapshot {
+            if let Err(restore_err) = restore_kiro_settings(snapshot.as_deref()) {
+                println!("[Switch] Failed to roll back settings.json: {}", restore_err);
+            }
+        }
+        return Err(e);
+    }
+    
+    // 5. 切换完成
+    let kiro_restarted = if kiro_was_running && should_restart {
+        launch_kiro(&LaunchOptions::with_workspace(last_workspace.clone())).is_ok()
+    } else {
+        false
+    };
+    
+    Ok(SwitchAccountResult {
+        success: true,
+        message: format!("Switched to {} ({}) account", provider, auth_method),
+        kiro_was_running,
+        kiro_restarted,
+        shutdown,
+        settings_profile: settings_profile.map(|p| p.name),
+        reopened_workspace: last_workspace.filter(|_| kiro_restarted),
+    })
+}
+
+/// 切换 Kiro 账号（直接写入 Token 文件，仅重置机器ID时才关闭IDE）
+#[tauri::command]
+pub async fn switch_kiro_account(params: SwitchAccountParams) -> Result<SwitchAccountResult, String> {
+    // 使用 spawn_blocking 避免阻塞异步运行时
+    tokio::task::spawn_blocking(move || {
+        let installation_id = params.installation_id.clone();
+        crate::installations::with_installation(installation_id.as_deref(), || switch_kiro_account_inner(params))
     }).await.map_err(|e| format!("Task failed: {}", e))?
 }
 
@@ -343,17 +519,14 @@ fn generate_dev_device_id() -> String {
 
 /// 重置机器 ID（内部函数）
 fn reset_kiro_machine_id_inner() -> Result<KiroTelemetryInfo, String> {
-    let kiro_dir = get_kiro_data_dir()
+    let paths = kiro_paths()
         .ok_or("Cannot find Kiro data directory")?;
     
     let new_machine_id = generate_machine_id();
     let new_sqm_id = generate_sqm_id();
     let new_dev_device_id = generate_dev_device_id();
     
-    let storage_path = kiro_dir
-        .join("User")
-        .join("globalStorage")
-        .join("storage.json");
+    let storage_path = paths.storage_file();
     
     let content = std::fs::read_to_string(&storage_path)
         .map_err(|e| format!("Failed to read storage.json: {}", e))?;
@@ -371,10 +544,7 @@ fn reset_kiro_machine_id_inner() -> Result<KiroTelemetryInfo, String> {
     std::fs::write(&storage_path, new_content)
         .map_err(|e| format!("Failed to write storage.json: {}", e))?;
     
-    let db_path = kiro_dir
-        .join("User")
-        .join("globalStorage")
-        .join("state.vscdb");
+    let db_path = paths.state_db_file();
     
     let mut new_service_machine_id = None;
     if db_path.exists() {
@@ -1,10 +1,11 @@
 use crate::browser::open_browser;
+use crate::http_client::{send_with_retry, RetryPolicy};
 use reqwest::Client;
 use serde::Deserialize;
 use std::time::Duration;
 
 /// Kiro Authentication Service Client
-/// 负责与 https://prod.us-east-1.auth.desktop.kiro.dev 通信
+/// 负责与 Kiro 桌面端认证服务（endpoints.desktop_auth）通信
 pub struct KiroAuthServiceClient {
     endpoint: String,
     client: Client,
@@ -12,11 +13,10 @@ pub struct KiroAuthServiceClient {
 
 impl KiroAuthServiceClient {
     pub fn new() -> Self {
-        let endpoint = "https://prod.us-east-1.auth.desktop.kiro.dev".to_string();
+        let endpoint = crate::endpoints::endpoints().desktop_auth;
 
-        let client = Client::builder()
+        let client = crate::http_client::client_builder(Some("KiroBatchLoginCLI/1.0.0"))
             .timeout(Duration::from_millis(10_000))
-            .user_agent("KiroBatchLoginCLI/1.0.0")
             .build()
             .expect("failed to build reqwest client");
 
@@ -95,11 +95,11 @@ impl KiroAuthServiceClient {
             invitation_code,
         };
 
-        let resp = self
+        let request = self
             .client
             .post(self.create_token_url())
-            .json(&body)
-            .send()
+            .json(&body);
+        let resp = send_with_retry(request, RetryPolicy::NonIdempotent, "kiroAuth.createToken")
             .await
             .map_err(|e| format!("Kiro Auth Service request failed: {}", e))?;
 
@@ -158,11 +158,11 @@ impl KiroAuthServiceClient {
 
         let body = Body { refresh_token };
 
-        let resp = self
+        let request = self
             .client
             .post(self.refresh_token_url())
-            .json(&body)
-            .send()
+            .json(&body);
+        let resp = send_with_retry(request, RetryPolicy::NonIdempotent, "kiroAuth.refreshToken")
             .await
             .map_err(|e| format!("Kiro Auth Service request failed: {}", e))?;
 
@@ -0,0 +1,278 @@
+// Kiro IDE 本地诊断（只读）
+// 汇总 IDE 版本、state.vscdb 中 IDE 记录的登录状态、最近工作区、扩展数量，
+// 并与 kiro-auth-token.json 对比，标出两边不一致的地方。
+// state.vscdb 的登录状态由 Kiro 扩展自行存储，键名和结构没有文档，这里按字段名宽松匹配
+
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::kiro::{get_kiro_local_token, recent_kiro_workspaces, KiroLocalToken, RecentWorkspace};
+use crate::kiro_paths::{kiro_paths, KiroPaths};
+
+/// 搜索 JSON 时的最大深度
+const MAX_SEARCH_DEPTH: usize = 6;
+
+/// IDE 认为的登录状态（来自 state.vscdb）
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdeAuthState {
+    pub signed_in: Option<bool>,
+    pub provider: Option<String>,
+    pub auth_method: Option<String>,
+    pub email: Option<String>,
+    pub last_sign_in: Option<String>,
+    /// 读取到登录信息的 ItemTable 键
+    pub source_keys: Vec<String>,
+}
+
+/// 本地 Token 文件摘要（不返回 Token 本身）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenFileSummary {
+    pub provider: Option<String>,
+    pub auth_method: Option<String>,
+    pub expires_at: Option<String>,
+    pub expired: bool,
+    pub modified_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticIssue {
+    /// warning / error
+    pub level: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroDiagnostics {
+    pub ide_version: Option<String>,
+    pub data_dir: String,
+    pub state_db_found: bool,
+    pub ide_auth: IdeAuthState,
+    pub token_file: Option<TokenFileSummary>,
+    pub recent_workspaces: Vec<RecentWorkspace>,
+    pub extension_count: Option<usize>,
+    pub disabled_extension_count: Option<usize>,
+    pub issues: Vec<DiagnosticIssue>,
+}
+
+fn issue(level: &str, message: impl Into<String>) -> DiagnosticIssue {
+    DiagnosticIssue { level: level.to_string(), message: message.into() }
+}
+
+/// 只读打开 state.vscdb（IDE 正在写入时最多等 2 秒）
+fn open_state_db(path: &Path) -> Option<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    ).ok()?;
+    conn.busy_timeout(Duration::from_secs(2)).ok()?;
+    Some(conn)
+}
+
+/// IDE 版本: 安装包 resources/app/package.json 的 version
+fn ide_version() -> Option<String> {
+    let installation = crate::installations::current_installation()
+        .or_else(|| crate::installations::detect_installations().into_iter().next())?;
+    let app_dir = if cfg!(target_os = "macos") {
+        installation.install_dir().join("Contents").join("Resources").join("app")
+    } else {
+        installation.install_dir().join("resources").join("app")
+    };
+    let content = std::fs::read_to_string(app_dir.join("package.json")).ok()?;
+    let json: Value = serde_json::from_str(&content).ok()?;
+    json.get("version").and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// 在 JSON 中按字段名（不区分大小写）查找第一个匹配的值
+fn find_field<'a>(value: &'a Value, names: &[&str], depth: usize) -> Option<&'a Value> {
+    if depth > MAX_SEARCH_DEPTH {
+        return None;
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                if names.iter().any(|n| key.eq_ignore_ascii_case(n)) && !v.is_null() {
+                    return Some(v);
+                }
+            }
+            map.values().find_map(|v| find_field(v, names, depth + 1))
+        }
+        Value::Array(items) => items.iter().find_map(|v| find_field(v, names, depth + 1)),
+        _ => None,
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => n.as_i64().map(timestamp_to_string).or_else(|| Some(n.to_string())),
+        _ => None,
+    }
+}
+
+/// 毫秒 / 秒时间戳转为本地时间
+fn timestamp_to_string(ts: i64) -> String {
+    let secs = if ts > 10_000_000_000 { ts / 1000 } else { ts };
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|dt| dt.with_timezone(&chrono::Local).format("%Y/%m/%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+/// 从 Kiro 扩展存储的状态中提取登录信息
+fn read_ide_auth(conn: &Connection) -> IdeAuthState {
+    let mut state = IdeAuthState::default();
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT key, value FROM ItemTable WHERE lower(key) LIKE '%kiro%' OR lower(key) LIKE '%auth%'"
+    ) else {
+        return state;
+    };
+    let Ok(rows) = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))) else {
+        return state;
+    };
+
+    for (key, raw) in rows.flatten() {
+        let Ok(json) = serde_json::from_str::<Value>(&raw) else { continue };
+        let before = (state.signed_in, state.provider.clone(), state.auth_method.clone(), state.email.clone(), state.last_sign_in.clone());
+
+        if state.signed_in.is_none() {
+            state.signed_in = find_field(&json, &["isSignedIn", "signedIn", "isLoggedIn", "loggedIn", "isAuthenticated"], 0)
+                .and_then(|v| v.as_bool());
+        }
+        if state.provider.is_none() {
+            state.provider = find_field(&json, &["provider", "authProvider", "loginProvider"], 0).and_then(value_to_string);
+        }
+        if state.auth_method.is_none() {
+            state.auth_method = find_field(&json, &["authMethod", "authType", "loginType"], 0).and_then(value_to_string);
+        }
+        if state.email.is_none() {
+            state.email = find_field(&json, &["email", "userEmail"], 0).and_then(value_to_string);
+        }
+        if state.last_sign_in.is_none() {
+            state.last_sign_in = find_field(&json, &["lastSignIn", "lastSignInTime", "lastLogin", "lastLoginTime", "signedInAt", "loginTime"], 0)
+                .and_then(value_to_string);
+        }
+
+        let after = (state.signed_in, state.provider.clone(), state.auth_method.clone(), state.email.clone(), state.last_sign_in.clone());
+        if before != after {
+            state.source_keys.push(key);
+        }
+    }
+    state
+}
+
+/// 禁用的扩展数量（extensionsIdentifiers/disabled）
+fn disabled_extension_count(conn: &Connection) -> Option<usize> {
+    let raw: String = conn.query_row(
+        "SELECT value FROM ItemTable WHERE key = 'extensionsIdentifiers/disabled'",
+        [],
+        |row| row.get(0),
+    ).ok()?;
+    serde_json::from_str::<Vec<Value>>(&raw).ok().map(|v| v.len())
+}
+
+/// 已安装扩展数量: ~/.kiro/extensions/extensions.json，没有时数目录
+fn extension_count(paths: &KiroPaths) -> Option<usize> {
+    let dir = paths.home_dir.join("extensions");
+    if let Some(entries) = std::fs::read_to_string(dir.join("extensions.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Vec<Value>>(&content).ok())
+    {
+        return Some(entries.len());
+    }
+    let entries = std::fs::read_dir(&dir).ok()?;
+    Some(entries.flatten().filter(|e| e.path().is_dir() && !e.file_name().to_string_lossy().starts_with('.')).count())
+}
+
+fn token_file_summary(paths: &KiroPaths, token: &KiroLocalToken) -> TokenFileSummary {
+    let expires = token.expires_at.as_deref().and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok());
+    let modified_at = std::fs::metadata(paths.auth_token_file())
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|t| chrono::DateTime::<chrono::Local>::from(t).format("%Y/%m/%d %H:%M:%S").to_string());
+    TokenFileSummary {
+        provider: token.provider.clone(),
+        auth_method: token.auth_method.clone(),
+        expires_at: token.expires_at.clone(),
+        expired: expires.is_some_and(|e| e < chrono::Utc::now()),
+        modified_at,
+    }
+}
+
+/// 对比 IDE 登录状态和 Token 文件
+fn compare_auth(ide: &IdeAuthState, token: Option<&TokenFileSummary>) -> Vec<DiagnosticIssue> {
+    let mut issues = Vec::new();
+    match (ide.signed_in, token) {
+        (Some(true), None) => issues.push(issue("error", "IDE 显示已登录，但 kiro-auth-token.json 不存在")),
+        (Some(false), Some(_)) => issues.push(issue("warning", "kiro-auth-token.json 存在，但 IDE 显示未登录（可能需要重启 IDE）")),
+        _ => {}
+    }
+    let Some(token) = token else {
+        return issues;
+    };
+    if token.expired {
+        issues.push(issue("warning", "kiro-auth-token.json 中的 Token 已过期，IDE 会在下次请求时刷新"));
+    }
+    let differs = |a: &Option<String>, b: &Option<String>| {
+        matches!((a, b), (Some(a), Some(b)) if !a.eq_ignore_ascii_case(b))
+    };
+    if differs(&ide.provider, &token.provider) {
+        issues.push(issue("error", format!(
+            "登录方式不一致: IDE 为 {}，Token 文件为 {}",
+            ide.provider.as_deref().unwrap_or_default(),
+            token.provider.as_deref().unwrap_or_default(),
+        )));
+    }
+    if differs(&ide.auth_method, &token.auth_method) {
+        issues.push(issue("error", format!(
+            "认证类型不一致: IDE 为 {}，Token 文件为 {}",
+            ide.auth_method.as_deref().unwrap_or_default(),
+            token.auth_method.as_deref().unwrap_or_default(),
+        )));
+    }
+    issues
+}
+
+fn get_kiro_diagnostics_inner() -> Result<KiroDiagnostics, String> {
+    let paths = kiro_paths().ok_or("Cannot find home directory")?;
+    let db_path = paths.state_db_file();
+    let conn = if db_path.exists() { open_state_db(&db_path) } else { None };
+
+    let mut issues = Vec::new();
+    if !db_path.exists() {
+        issues.push(issue("warning", format!("未找到 state.vscdb: {}", db_path.display())));
+    } else if conn.is_none() {
+        issues.push(issue("warning", "state.vscdb 无法打开（可能被 IDE 锁定）"));
+    }
+
+    let ide_auth = conn.as_ref().map(read_ide_auth).unwrap_or_default();
+    let token_file = get_kiro_local_token().map(|t| token_file_summary(&paths, &t));
+    issues.extend(compare_auth(&ide_auth, token_file.as_ref()));
+
+    Ok(KiroDiagnostics {
+        ide_version: ide_version(),
+        data_dir: paths.data_dir.to_string_lossy().to_string(),
+        state_db_found: db_path.exists(),
+        recent_workspaces: recent_kiro_workspaces(),
+        extension_count: extension_count(&paths),
+        disabled_extension_count: conn.as_ref().and_then(disabled_extension_count),
+        ide_auth,
+        token_file,
+        issues,
+    })
+}
+
+/// 读取 Kiro IDE 本地诊断信息（只读，不修改任何文件）
+#[tauri::command]
+pub async fn get_kiro_diagnostics(installation_id: Option<String>) -> Result<KiroDiagnostics, String> {
+    tokio::task::spawn_blocking(move || {
+        crate::installations::with_installation(installation_id.as_deref(), get_kiro_diagnostics_inner)
+    })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
@@ -0,0 +1,180 @@
+// Kiro IDE 文件路径解析
+// 统一处理 Windows / macOS / Linux（XDG、Flatpak 沙盒、便携版）下的目录差异，
+// 其他模块不再各自拼接路径。AppSettings.kiro_data_dir 可以覆盖自动探测结果；
+// 选中了某个安装（见 installations）时按该安装解析
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::installations::KiroInstallation;
+
+/// Kiro 的 Flatpak 应用 ID
+pub const FLATPAK_APP_IDS: &[&str] = &["dev.kiro.Kiro", "dev.kiro.kiro"];
+
+/// 已解析的 Kiro 路径
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroPaths {
+    /// IDE 用户数据目录（包含 User/settings.json、User/globalStorage）
+    pub data_dir: PathBuf,
+    /// ~/.kiro（MCP 配置、Powers、Steering）
+    pub home_dir: PathBuf,
+    /// ~/.aws/sso/cache（登录 Token 和客户端注册）
+    pub sso_cache_dir: PathBuf,
+    /// 数据目录来源: override / portable / flatpak / default
+    pub data_dir_source: String,
+}
+
+impl KiroPaths {
+    pub fn user_dir(&self) -> PathBuf {
+        self.data_dir.join("User")
+    }
+
+    pub fn settings_file(&self) -> PathBuf {
+        self.user_dir().join("settings.json")
+    }
+
+    pub fn global_storage_dir(&self) -> PathBuf {
+        self.user_dir().join("globalStorage")
+    }
+
+    pub fn storage_file(&self) -> PathBuf {
+        self.global_storage_dir().join("storage.json")
+    }
+
+    pub fn state_db_file(&self) -> PathBuf {
+        self.global_storage_dir().join("state.vscdb")
+    }
+
+    pub fn mcp_config_file(&self) -> PathBuf {
+        self.home_dir.join("settings").join("mcp.json")
+    }
+
+    pub fn powers_dir(&self) -> PathBuf {
+        self.home_dir.join("powers")
+    }
+
+    pub fn steering_dir(&self) -> PathBuf {
+        self.home_dir.join("steering")
+    }
+
+    pub fn auth_token_file(&self) -> PathBuf {
+        self.sso_cache_dir.join("kiro-auth-token.json")
+    }
+
+    pub fn client_registration_file(&self, client_id_hash: &str) -> PathBuf {
+        self.sso_cache_dir.join(format!("{}.json", client_id_hash))
+    }
+}
+
+/// 平台默认的数据目录（name 为产品名，正式版是 "Kiro"）
+fn default_data_dir(home: &Path, name: &str) -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join("AppData").join("Roaming"))
+            .join(name)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        home.join("Library").join("Application Support").join(name)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .unwrap_or_else(|| home.join(".config"))
+            .join(name)
+    }
+}
+
+/// 便携版数据目录: KIRO_PORTABLE 环境变量，或设置中安装目录下的 data/user-data
+fn portable_data_dir(install_paths: &[String]) -> Option<PathBuf> {
+    if let Some(portable) = std::env::var_os("KIRO_PORTABLE") {
+        return Some(PathBuf::from(portable).join("user-data"));
+    }
+    install_paths.iter()
+        .map(PathBuf::from)
+        .filter_map(|p| if p.is_dir() { Some(p) } else { p.parent().map(Path::to_path_buf) })
+        .map(|dir| dir.join("data").join("user-data"))
+        .find(|dir| dir.is_dir())
+}
+
+/// Flatpak 沙盒中的数据目录 (~/.var/app/<id>/config/Kiro)
+#[cfg(target_os = "linux")]
+fn flatpak_data_dir(home: &Path) -> Option<PathBuf> {
+    FLATPAK_APP_IDS.iter()
+        .map(|id| home.join(".var").join("app").join(id).join("config").join("Kiro"))
+        .find(|dir| dir.is_dir())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn flatpak_data_dir(_home: &Path) -> Option<PathBuf> {
+    None
+}
+
+/// 指定安装的路径: 安装的数据目录覆盖 > 便携版 > product.json 中的产品名
+fn installation_paths(installation: &KiroInstallation, home: &Path) -> KiroPaths {
+    let product = installation.product_info();
+    let (data_dir, source) = if let Some(dir) = installation.data_dir.as_deref().filter(|d| !d.trim().is_empty()) {
+        (PathBuf::from(dir), "override")
+    } else if let Some(dir) = installation.portable_data_dir() {
+        (dir, "portable")
+    } else {
+        let name = product.name_short.as_deref().unwrap_or("Kiro");
+        (default_data_dir(home, name), "installation")
+    };
+
+    KiroPaths {
+        data_dir,
+        home_dir: home.join(product.data_folder_name.as_deref().unwrap_or(".kiro")),
+        sso_cache_dir: home.join(".aws").join("sso").join("cache"),
+        data_dir_source: source.to_string(),
+    }
+}
+
+/// 解析 Kiro 路径（installation 为 None 时: 设置覆盖 > 便携版 > Flatpak > 平台默认）
+pub fn kiro_paths_for(installation: Option<&KiroInstallation>) -> Option<KiroPaths> {
+    let home = dirs::home_dir()?;
+    if let Some(installation) = installation {
+        return Some(installation_paths(installation, &home));
+    }
+
+    let settings = crate::commands::app_settings_cmd::load_app_settings();
+
+    let (data_dir, source) = if let Some(dir) = settings.kiro_data_dir.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        (PathBuf::from(dir), "override")
+    } else if let Some(dir) = portable_data_dir(settings.kiro_install_paths.as_deref().unwrap_or_default()) {
+        (dir, "portable")
+    } else if let Some(dir) = flatpak_data_dir(&home) {
+        (dir, "flatpak")
+    } else {
+        (default_data_dir(&home, "Kiro"), "default")
+    };
+
+    Some(KiroPaths {
+        data_dir,
+        home_dir: home.join(".kiro"),
+        sso_cache_dir: home.join(".aws").join("sso").join("cache"),
+        data_dir_source: source.to_string(),
+    })
+}
+
+/// 当前目标安装（见 installations::current_installation）的路径
+pub fn kiro_paths() -> Option<KiroPaths> {
+    kiro_paths_for(crate::installations::current_installation().as_ref())
+}
+
+/// 获取当前解析出的 Kiro 路径（设置页展示 / 排查问题用）
+#[tauri::command]
+pub async fn get_kiro_paths(installation_id: Option<String>) -> Result<KiroPaths, String> {
+    tokio::task::spawn_blocking(move || {
+        crate::installations::with_installation(installation_id.as_deref(), || {
+            kiro_paths().ok_or("Cannot find home directory".to_string())
+        })
+    })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
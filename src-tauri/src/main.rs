@@ -9,14 +9,24 @@ mod bt_mail;
 mod codewhisperer_client;
 mod commands;
 mod deep_link_handler;
+mod endpoints;
+mod http_client;
+mod installations;
+mod jsonc;
 
 mod kiro;
 mod kiro_auth_client;
+mod kiro_diagnostics;
+mod kiro_paths;
 mod mail_db;
 mod mcp;
 mod powers;
 mod process;
 mod providers;
+mod settings_watcher;
+mod tray;
+#[cfg(test)]
+mod tests;
 mod state;
 mod steering;
 mod account;
@@ -32,11 +42,13 @@ use browser::detect_installed_browsers;
 use commands::account_cmd::{
     get_accounts, delete_account, delete_accounts, update_account, sync_account,
     refresh_account_token, verify_account, add_account_by_social, add_local_kiro_account,
-    add_account_by_idc, import_accounts, export_accounts
+    add_account_by_idc, import_accounts, export_accounts, get_idc_client_status,
+    reauthorize_idc_account
 };
 use commands::app_settings_cmd::*;
 use commands::auth_cmd::*;
 use commands::kiro_settings_cmd::*;
+use commands::settings_profile_cmd::*;
 use commands::machine_guid_cmd::*;
 use commands::mcp_cmd::*;
 use commands::powers_cmd::*;
@@ -48,9 +60,13 @@ use commands::steering_cmd::*;
 use commands::mail_cmd::*;
 // use commands::cloud_sync_cmd::*; // 已迁移到前端实现
 use kiro::{
-    get_kiro_local_token, get_kiro_telemetry_info, reset_kiro_machine_id, switch_kiro_account,
+    get_kiro_local_token, get_kiro_recent_workspaces, get_kiro_telemetry_info, reset_kiro_machine_id, switch_kiro_account,
 };
+use installations::{list_kiro_installations, register_kiro_installation, remove_kiro_installation, set_active_kiro_installation};
+use kiro_diagnostics::get_kiro_diagnostics;
+use kiro_paths::get_kiro_paths;
 use process::{close_kiro_ide, is_kiro_ide_running, start_kiro_ide};
+use tray::refresh_tray_menu;
 
 fn main() {
     tauri::Builder::default()
@@ -83,8 +99,25 @@ fn main() {
                 }
             });
             
+            // 监听 Kiro settings.json，恢复被改动的锁定模型 / 固定设置项
+            settings_watcher::start(app.handle().clone());
+            
+            // 系统托盘（账号列表、快速切换）
+            if let Err(e) = tray::init(app.handle()) {
+                println!("[Tray] Failed to initialize: {}", e);
+            }
+            
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // 开启关闭到托盘时，关闭主窗口只隐藏，后台刷新继续运行
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.label() == "main" && tray::close_to_tray_enabled() {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
         .manage(AppState {
             store: Mutex::new(AccountStore::new()),
             auth: AuthState::new(),
@@ -106,6 +139,8 @@ fn main() {
             add_account_by_idc,
             import_accounts,
             export_accounts,
+            get_idc_client_status,
+            reauthorize_idc_account,
             // Auth 命令
             get_current_user,
             logout,
@@ -117,6 +152,14 @@ fn main() {
             get_kiro_local_token,
             switch_kiro_account,
             get_kiro_telemetry_info,
+            get_kiro_recent_workspaces,
+            get_kiro_diagnostics,
+            get_kiro_paths,
+            // Kiro 安装管理命令
+            list_kiro_installations,
+            register_kiro_installation,
+            remove_kiro_installation,
+            set_active_kiro_installation,
             reset_kiro_machine_id,
             // 进程管理命令
             close_kiro_ide,
@@ -126,6 +169,21 @@ fn main() {
             get_kiro_settings,
             set_kiro_proxy,
             set_kiro_model,
+            get_kiro_setting,
+            get_all_kiro_settings,
+            set_kiro_setting,
+            remove_kiro_setting,
+            list_kiro_settings_backups,
+            restore_kiro_settings_backup,
+            // Kiro 设置配置命令
+            get_settings_profiles,
+            save_settings_profile,
+            delete_settings_profile,
+            capture_settings_profile,
+            apply_settings_profile,
+            set_account_settings_profile,
+            // 系统托盘命令
+            refresh_tray_menu,
             // 应用设置命令
             get_app_settings,
             save_app_settings,
@@ -156,6 +214,7 @@ fn main() {
             save_mcp_server,
             delete_mcp_server,
             toggle_mcp_server,
+            validate_mcp_server,
             // Powers 管理命令
             get_powers_registry,
             get_installed_powers,
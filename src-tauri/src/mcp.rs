@@ -1,10 +1,13 @@
 // MCP 配置文件读写
+// mcp.json 允许注释和尾逗号，并且可能包含本程序不认识的字段（超时、headers、cwd、自定义键等），
+// 所以修改时只对原始文本做定点修改（见 jsonc.rs），不重新序列化整个文件；
+// 类型化的 McpServer 只用于展示和校验
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct McpConfig {
@@ -17,150 +20,309 @@ pub struct McpConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PowersMcpConfig {
     #[serde(rename = "mcpServers", default)]
-    pub mcp_servers: HashMap<String, PowerMcpServer>,
+    pub mcp_servers: HashMap<String, McpServer>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum McpServer {
-    Command(McpServerCommand),
-    Url(McpServerUrl),
+/// 传输方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransport {
+    /// 启动本地进程，通过 stdin / stdout 通信
+    Stdio,
+    /// 远程服务（streamable HTTP / SSE）
+    Http,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct McpServerCommand {
-    pub command: String,
-    #[serde(default)]
+/// MCP 服务器配置（命令型和 URL 型共用一个结构）
+/// 没有建模的字段原样保存在 extra 中
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServer {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub env: HashMap<String, String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "is_false")]
     pub disabled: bool,
-    #[serde(default, rename = "autoApprove")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub auto_approve: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tools: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct McpServerUrl {
-    pub url: String,
-    #[serde(default)]
-    pub disabled: bool,
-    #[serde(default, rename = "disabledTools")]
-    pub disabled_tools: Vec<String>,
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PowerMcpServer {
-    pub url: String,
-    #[serde(default)]
-    pub disabled: bool,
-    #[serde(default, rename = "disabledTools")]
-    pub disabled_tools: Vec<String>,
+/// 空值（缺省时等价于不写）
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+impl McpServer {
+    /// 从 JSON 解析；字段类型不符合预期时（如 env 值为数字）整个对象放进 extra，保证能正常展示
+    pub fn from_value(value: &Value) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_else(|_| Self {
+            extra: value.as_object().cloned().unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+
+    /// 传输方式: 显式的 type 字段优先，否则有 command 即为 stdio、有 url 即为 http
+    pub fn transport(&self) -> Option<McpTransport> {
+        let declared = self.extra.get("type").and_then(|v| v.as_str());
+        match (declared, &self.command, &self.url) {
+            (Some("stdio"), Some(_), _) => Some(McpTransport::Stdio),
+            (Some("http" | "sse" | "streamable-http" | "streamableHttp"), _, Some(_)) => Some(McpTransport::Http),
+            (_, Some(_), _) => Some(McpTransport::Stdio),
+            (_, None, Some(_)) => Some(McpTransport::Http),
+            _ => None,
+        }
+    }
+
+    /// 校验配置（保存前调用）
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err("服务器名称不能为空".to_string());
+        }
+        if name.chars().any(|c| c.is_control()) {
+            return Err("服务器名称不能包含控制字符".to_string());
+        }
+        if self.command.is_some() && self.url.is_some() && !self.extra.contains_key("type") {
+            return Err("不能同时配置 command 和 url（如确有需要，请用 type 字段指定传输方式）".to_string());
+        }
+        match self.transport() {
+            None => return Err("必须配置 command 或 url".to_string()),
+            Some(McpTransport::Stdio) => {
+                if !matches!(self.command.as_deref(), Some(c) if !c.trim().is_empty()) {
+                    return Err("command 不能为空".to_string());
+                }
+            }
+            Some(McpTransport::Http) => {
+                let raw = self.url.as_deref().unwrap_or_default().trim();
+                let parsed = url::Url::parse(raw).map_err(|e| format!("url 无效: {}", e))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(format!("url 必须是 http 或 https 地址: {}", raw));
+                }
+            }
+        }
+        if self.env.keys().any(|k| k.trim().is_empty()) {
+            return Err("环境变量名不能为空".to_string());
+        }
+        if self.headers.keys().any(|k| k.trim().is_empty()) {
+            return Err("请求头名称不能为空".to_string());
+        }
+        Ok(())
+    }
+
+    /// 已建模字段在文件中的值（None 表示不写该键）
+    fn known_fields(&self) -> Vec<(&'static str, Option<Value>)> {
+        fn non_empty(value: Value) -> Option<Value> {
+            (!is_empty_value(&value)).then_some(value)
+        }
+        vec![
+            ("command", self.command.clone().map(Value::String)),
+            ("args", non_empty(serde_json::json!(self.args))),
+            ("env", non_empty(serde_json::json!(self.env))),
+            ("url", self.url.clone().map(Value::String)),
+            ("headers", non_empty(serde_json::json!(self.headers))),
+            ("disabled", non_empty(Value::Bool(self.disabled))),
+            ("autoApprove", non_empty(serde_json::json!(self.auto_approve))),
+            ("disabledTools", non_empty(serde_json::json!(self.disabled_tools))),
+        ]
+    }
+
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or_else(|_| Value::Object(Map::new()))
+    }
+
+    /// 与文件中已有的配置对比，生成最小修改（路径相对于服务器对象）
+    /// 未建模的字段只增改不删除，已建模的字段为空且文件中也为空时保持原样
+    fn diff(&self, existing: &Map<String, Value>) -> Vec<(String, Option<Value>)> {
+        let mut changes = Vec::new();
+        for (key, desired) in self.known_fields() {
+            let current = existing.get(key);
+            match desired {
+                Some(value) if current != Some(&value) => changes.push((key.to_string(), Some(value))),
+                None if current.is_some_and(|c| !is_empty_value(c)) => changes.push((key.to_string(), None)),
+                _ => {}
+            }
+        }
+        for (key, value) in &self.extra {
+            if existing.get(key) != Some(value) {
+                changes.push((key.clone(), Some(value.clone())));
+            }
+        }
+        changes
+    }
+}
+
+fn parse_servers(value: Option<&Value>) -> HashMap<String, McpServer> {
+    value
+        .and_then(|v| v.as_object())
+        .map(|servers| servers.iter().map(|(name, v)| (name.clone(), McpServer::from_value(v))).collect())
+        .unwrap_or_default()
+}
+
+/// 原子写入（先写临时文件再重命名，避免 IDE 读到写了一半的文件）
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content)
+        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("写入配置文件失败: {}", e)
+    })
 }
 
 impl McpConfig {
     /// 获取 MCP 配置文件路径
     pub fn config_path() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".kiro").join("settings").join("mcp.json"))
+        crate::kiro_paths::kiro_paths().map(|p| p.mcp_config_file())
     }
 
-    /// 读取配置文件（保留原始 JSON）
-    pub fn load_raw() -> Result<Value, String> {
+    /// 读取原始文本（文件不存在时为 None）
+    pub fn load_text() -> Result<Option<String>, String> {
         let path = Self::config_path().ok_or("无法获取用户目录")?;
-        
         if !path.exists() {
-            return Ok(serde_json::json!({"mcpServers": {}}));
+            return Ok(None);
+        }
+        fs::read_to_string(&path)
+            .map(Some)
+            .map_err(|e| format!("读取配置文件失败: {}", e))
+    }
+
+    /// 读取配置文件（保留原始 JSON）
+    pub fn load_raw() -> Result<Value, String> {
+        match Self::load_text()? {
+            Some(content) => crate::jsonc::parse(&content)
+                .map_err(|e| format!("解析配置文件失败: {}", e)),
+            None => Ok(serde_json::json!({"mcpServers": {}})),
         }
-        
-        let content = fs::read_to_string(&path)
-            .map_err(|e| format!("读取配置文件失败: {}", e))?;
-        
-        serde_json::from_str(&content)
-            .map_err(|e| format!("解析配置文件失败: {}", e))
     }
 
     /// 读取配置文件
     pub fn load() -> Result<Self, String> {
-        let path = Self::config_path().ok_or("无法获取用户目录")?;
-        
-        if !path.exists() {
-            return Ok(Self::default());
-        }
-        
-        let content = fs::read_to_string(&path)
-            .map_err(|e| format!("读取配置文件失败: {}", e))?;
-        
-        serde_json::from_str(&content)
-            .map_err(|e| format!("解析配置文件失败: {}", e))
+        let raw = Self::load_raw()?;
+        let powers = raw.get("powers").map(|p| PowersMcpConfig {
+            mcp_servers: parse_servers(p.get("mcpServers")),
+        });
+        Ok(Self {
+            mcp_servers: parse_servers(raw.get("mcpServers")),
+            powers,
+        })
     }
 
-    /// 保存原始 JSON
-    pub fn save_raw(value: &Value) -> Result<(), String> {
+    /// 对原始文本做定点修改（路径从根对象开始，None 表示删除），注释、键顺序和未知字段保持不变
+    /// 返回是否真的发生了修改
+    pub fn patch(changes: &[(Vec<String>, Option<Value>)]) -> Result<bool, String> {
         let path = Self::config_path().ok_or("无法获取用户目录")?;
-        
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("创建目录失败: {}", e))?;
+        let original = Self::load_text()?;
+        let source = original.clone().unwrap_or_default();
+
+        // 解析失败时拒绝写入，避免覆盖掉用户的配置
+        crate::jsonc::parse(&source)
+            .map_err(|e| format!("MCP 配置文件格式错误，已拒绝写入（请先手动修复 {}）: {}", path.display(), e))?;
+
+        let mut updated = crate::jsonc::set_many(&source, changes)?;
+        if original.is_none() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        if original.as_deref() == Some(updated.as_str()) {
+            return Ok(false);
         }
-        
-        let content = serde_json::to_string_pretty(value)
-            .map_err(|e| format!("序列化配置失败: {}", e))?;
-        
-        fs::write(&path, content)
-            .map_err(|e| format!("写入配置文件失败: {}", e))
+        crate::jsonc::parse(&updated).map_err(|e| format!("生成的 MCP 配置无效: {}", e))?;
+
+        write_atomic(&path, &updated)?;
+        Ok(true)
     }
 
-    /// 保存配置文件
-    pub fn save(&self) -> Result<(), String> {
-        let path = Self::config_path().ok_or("无法获取用户目录")?;
-        
-        // 确保目录存在
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("创建目录失败: {}", e))?;
+    /// 保存服务器配置: 已存在时只修改有变化的字段，不存在时整体插入
+    pub fn save_server(name: &str, server: &McpServer) -> Result<(), String> {
+        server.validate(name)?;
+        let raw = Self::load_raw()?;
+        let base = vec!["mcpServers".to_string(), name.to_string()];
+
+        let changes: Vec<(Vec<String>, Option<Value>)> = match raw.pointer("/mcpServers").and_then(|s| s.get(name)).and_then(|s| s.as_object()) {
+            Some(existing) => server.diff(existing)
+                .into_iter()
+                .map(|(key, value)| {
+                    let mut path = base.clone();
+                    path.push(key);
+                    (path, value)
+                })
+                .collect(),
+            None => vec![(base, Some(server.to_value()))],
+        };
+        Self::patch(&changes).map(|_| ())
+    }
+
+    /// 删除服务器
+    pub fn remove_server(name: &str) -> Result<(), String> {
+        Self::patch(&[(vec!["mcpServers".to_string(), name.to_string()], None)]).map(|_| ())
+    }
+
+    /// 修改服务器的单个字段（服务器必须已存在）
+    pub fn set_server_field(name: &str, key: &str, value: Option<Value>) -> Result<(), String> {
+        let raw = Self::load_raw()?;
+        if raw.pointer("/mcpServers").and_then(|s| s.get(name)).is_none() {
+            return Err(format!("服务器 {} 不存在", name));
         }
-        
-        let content = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("序列化配置失败: {}", e))?;
-        
-        fs::write(&path, content)
-            .map_err(|e| format!("写入配置文件失败: {}", e))
+        Self::patch(&[(vec!["mcpServers".to_string(), name.to_string(), key.to_string()], value)]).map(|_| ())
     }
 
     /// 添加 Power 的 MCP 配置
     pub fn add_power_mcp(power_name: &str, mcp_config: Value) -> Result<(), String> {
-        let mut raw = Self::load_raw()?;
-        
-        // 确保 powers.mcpServers 存在
-        if raw.get("powers").is_none() {
-            raw["powers"] = serde_json::json!({"mcpServers": {}});
-        }
-        if raw["powers"].get("mcpServers").is_none() {
-            raw["powers"]["mcpServers"] = serde_json::json!({});
-        }
-        
-        // 读取 power 的 mcp.json 并合并
-        if let Some(servers) = mcp_config.get("mcpServers").and_then(|s| s.as_object()) {
-            for (name, config) in servers {
-                let key = format!("power-{}-{}", power_name, name);
-                raw["powers"]["mcpServers"][key] = config.clone();
-            }
+        let changes: Vec<(Vec<String>, Option<Value>)> = mcp_config.get("mcpServers")
+            .and_then(|s| s.as_object())
+            .map(|servers| {
+                servers.iter()
+                    .map(|(name, config)| {
+                        let key = format!("power-{}-{}", power_name, name);
+                        (vec!["powers".to_string(), "mcpServers".to_string(), key], Some(config.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if changes.is_empty() {
+            return Ok(());
         }
-        
-        Self::save_raw(&raw)
+        Self::patch(&changes).map(|_| ())
     }
 
     /// 移除 Power 的 MCP 配置
     pub fn remove_power_mcp(power_name: &str) -> Result<(), String> {
-        let mut raw = Self::load_raw()?;
-        
-        if let Some(powers) = raw.get_mut("powers") {
-            if let Some(servers) = powers.get_mut("mcpServers").and_then(|s| s.as_object_mut()) {
-                let prefix = format!("power-{}-", power_name);
-                servers.retain(|k, _| !k.starts_with(&prefix));
-            }
+        let raw = Self::load_raw()?;
+        let prefix = format!("power-{}-", power_name);
+        let changes: Vec<(Vec<String>, Option<Value>)> = raw.pointer("/powers/mcpServers")
+            .and_then(|s| s.as_object())
+            .map(|servers| {
+                servers.keys()
+                    .filter(|k| k.starts_with(&prefix))
+                    .map(|k| (vec!["powers".to_string(), "mcpServers".to_string(), k.clone()], None))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if changes.is_empty() {
+            return Ok(());
         }
-        
-        Self::save_raw(&raw)
+        Self::patch(&changes).map(|_| ())
     }
 }
@@ -0,0 +1,593 @@
+// MCP 连接测试客户端
+// 按配置启动 / 连接 MCP 服务器，完成 initialize 握手并列出工具，用来确认服务器能否正常工作。
+// stdio: 启动子进程，每行一条 JSON-RPC 消息；
+// HTTP: 先尝试 streamable HTTP（POST，响应为 JSON 或 SSE），服务器不支持时退回旧版 SSE 传输
+// （GET 建立事件流，从 endpoint 事件拿到消息地址，响应从事件流返回）
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+use crate::mcp::{McpServer, McpTransport};
+
+const PROTOCOL_VERSION: &str = "2025-03-26";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// 最多保留的 stderr 输出
+const MAX_STDERR_BYTES: usize = 64 * 1024;
+/// tools/list 最多翻页次数
+const MAX_TOOL_PAGES: usize = 20;
+/// 关闭 stdin 后等待进程自行退出的时间
+const EXIT_GRACE: Duration = Duration::from_millis(1000);
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTool {
+    pub name: String,
+    pub description: Option<String>,
+    pub input_schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTestResult {
+    pub success: bool,
+    pub transport: Option<McpTransport>,
+    pub protocol_version: Option<String>,
+    /// initialize 响应中的 serverInfo（name / version）
+    pub server_info: Option<Value>,
+    pub capabilities: Option<Value>,
+    pub instructions: Option<String>,
+    pub tools: Vec<McpTool>,
+    /// 子进程 stderr 输出（stdio 服务器）
+    pub stderr: String,
+    pub error: Option<String>,
+    /// 从开始到 initialize 完成的耗时
+    pub initialize_ms: Option<u64>,
+    pub elapsed_ms: u64,
+}
+
+impl McpTestResult {
+    fn new(transport: Option<McpTransport>) -> Self {
+        Self {
+            success: false,
+            transport,
+            protocol_version: None,
+            server_info: None,
+            capabilities: None,
+            instructions: None,
+            tools: Vec::new(),
+            stderr: String::new(),
+            error: None,
+            initialize_ms: None,
+            elapsed_ms: 0,
+        }
+    }
+}
+
+/// 从 JSON-RPC 响应中取出 result
+fn extract_result(message: Value) -> Result<Value, String> {
+    if let Some(error) = message.get("error") {
+        let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or_default();
+        let text = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+        return Err(format!("服务器返回错误 ({}): {}", code, text));
+    }
+    Ok(message.get("result").cloned().unwrap_or(Value::Null))
+}
+
+fn is_response_to(message: &Value, id: u64) -> bool {
+    message.get("id").and_then(|v| v.as_u64()) == Some(id)
+        && (message.get("result").is_some() || message.get("error").is_some())
+}
+
+// ============================================================
+// stdio
+// ============================================================
+
+struct StdioSession {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: Lines<BufReader<ChildStdout>>,
+    stderr: Arc<Mutex<String>>,
+    stderr_task: Option<tokio::task::JoinHandle<()>>,
+    next_id: u64,
+}
+
+impl StdioSession {
+    fn spawn(server: &McpServer) -> Result<Self, String> {
+        let program = server.command.as_deref().map(str::trim).filter(|c| !c.is_empty()).ok_or("command 不能为空")?;
+
+        // Windows 上 npx / uvx 等是 .cmd 脚本，不能直接 CreateProcess，交给 cmd 解析
+        #[cfg(target_os = "windows")]
+        let mut command = if std::path::Path::new(program).extension().is_none() {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(program);
+            command
+        } else {
+            Command::new(program)
+        };
+        #[cfg(not(target_os = "windows"))]
+        let mut command = Command::new(program);
+
+        command
+            .args(&server.args)
+            .envs(&server.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(cwd) = server.extra.get("cwd").and_then(|v| v.as_str()).filter(|c| !c.is_empty()) {
+            command.current_dir(cwd);
+        }
+        // 独立进程组，结束时连同 npx 启动的子进程一起清理
+        #[cfg(unix)]
+        command.process_group(0);
+        #[cfg(target_os = "windows")]
+        command.creation_flags(CREATE_NO_WINDOW);
+
+        let mut child = command.spawn().map_err(|e| format!("启动 {} 失败: {}", program, e))?;
+        let stdin = child.stdin.take().ok_or("无法获取 stdin")?;
+        let stdout = child.stdout.take().ok_or("无法获取 stdout")?;
+        let mut stderr_pipe = child.stderr.take().ok_or("无法获取 stderr")?;
+
+        let stderr = Arc::new(Mutex::new(String::new()));
+        let sink = stderr.clone();
+        let stderr_task = tokio::spawn(async move {
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = stderr_pipe.read(&mut buf).await {
+                if n == 0 {
+                    break;
+                }
+                let mut text = sink.lock().unwrap();
+                if text.len() < MAX_STDERR_BYTES {
+                    text.push_str(&String::from_utf8_lossy(&buf[..n]));
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin: Some(stdin),
+            stdout: BufReader::new(stdout).lines(),
+            stderr,
+            stderr_task: Some(stderr_task),
+            next_id: 1,
+        })
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<(), String> {
+        let stdin = self.stdin.as_mut().ok_or("stdin 已关闭")?;
+        let mut line = message.to_string();
+        line.push('\n');
+        stdin.write_all(line.as_bytes()).await.map_err(|e| format!("写入 stdin 失败: {}", e))?;
+        stdin.flush().await.map_err(|e| format!("写入 stdin 失败: {}", e))
+    }
+
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})).await?;
+
+        loop {
+            let line = self.stdout.next_line().await.map_err(|e| format!("读取 stdout 失败: {}", e))?;
+            let Some(line) = line else {
+                let status = self.child.try_wait().ok().flatten();
+                return Err(match status {
+                    Some(status) => format!("服务器进程已退出 ({})", status),
+                    None => "服务器关闭了 stdout".to_string(),
+                });
+            };
+            // 有的服务器会往 stdout 打日志，不是 JSON 的行直接跳过
+            let Ok(message) = serde_json::from_str::<Value>(line.trim()) else { continue };
+            if is_response_to(&message, id) {
+                return extract_result(message);
+            }
+            // 服务器发来的请求（ping / roots/list 等）：ping 正常应答，其他返回不支持
+            if let (Some(request_id), Some(request_method)) = (message.get("id"), message.get("method").and_then(|m| m.as_str())) {
+                let reply = if request_method == "ping" {
+                    json!({"jsonrpc": "2.0", "id": request_id, "result": {}})
+                } else {
+                    json!({"jsonrpc": "2.0", "id": request_id, "error": {"code": -32601, "message": "Method not found"}})
+                };
+                self.send(&reply).await?;
+            }
+        }
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<(), String> {
+        self.send(&json!({"jsonrpc": "2.0", "method": method})).await
+    }
+
+    /// 结束进程（先关闭 stdin 让服务器自行退出，超时后结束整个进程树）
+    async fn shutdown(&mut self) -> String {
+        self.stdin.take();
+        if tokio::time::timeout(EXIT_GRACE, self.child.wait()).await.is_err() {
+            if let Some(pid) = self.child.id() {
+                kill_process_tree(pid);
+            }
+            let _ = self.child.kill().await;
+        }
+        if let Some(task) = self.stderr_task.take() {
+            let _ = tokio::time::timeout(Duration::from_millis(500), task).await;
+        }
+        self.stderr.lock().unwrap().clone()
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_tree(pid: u32) {
+    // 进程组 ID 等于 pid（spawn 时设置了 process_group(0)）
+    let _ = std::process::Command::new("kill")
+        .args(["-KILL", &format!("-{}", pid)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(target_os = "windows")]
+fn kill_process_tree(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status();
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn kill_process_tree(_pid: u32) {}
+
+// ============================================================
+// HTTP / SSE
+// ============================================================
+
+#[derive(Debug, Default)]
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+/// SSE 解析（按空行分隔事件，data 多行用换行拼接）
+#[derive(Default)]
+struct SseParser {
+    buf: Vec<u8>,
+}
+
+impl SseParser {
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend(chunk.iter().filter(|&&b| b != b'\r'));
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buf.drain(..pos + 2).collect();
+            let mut event = SseEvent::default();
+            let mut data_lines = Vec::new();
+            for line in String::from_utf8_lossy(&block).lines() {
+                let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match field {
+                    "event" => event.event = value.to_string(),
+                    "data" => data_lines.push(value.to_string()),
+                    _ => {}
+                }
+            }
+            if !data_lines.is_empty() {
+                event.data = data_lines.join("\n");
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+struct SseStream {
+    response: reqwest::Response,
+    parser: SseParser,
+    pending: VecDeque<SseEvent>,
+}
+
+impl SseStream {
+    fn new(response: reqwest::Response) -> Self {
+        Self { response, parser: SseParser::default(), pending: VecDeque::new() }
+    }
+
+    async fn next_event(&mut self) -> Result<Option<SseEvent>, String> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            match self.response.chunk().await.map_err(|e| format!("读取事件流失败: {}", e))? {
+                Some(bytes) => self.pending.extend(self.parser.push(&bytes)),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// 读取事件直到收到指定 id 的响应
+    async fn wait_response(&mut self, id: u64) -> Result<Value, String> {
+        while let Some(event) = self.next_event().await? {
+            if !event.event.is_empty() && event.event != "message" {
+                continue;
+            }
+            if let Ok(message) = serde_json::from_str::<Value>(&event.data) {
+                if is_response_to(&message, id) {
+                    return extract_result(message);
+                }
+            }
+        }
+        Err("事件流已关闭，未收到响应".to_string())
+    }
+}
+
+struct HttpSession {
+    client: reqwest::Client,
+    /// 配置中的地址
+    base_url: String,
+    /// 发送消息的地址（旧版 SSE 传输为 endpoint 事件给出的地址）
+    post_url: String,
+    headers: HeaderMap,
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+    /// 旧版 SSE 传输的事件流（响应从这里返回）
+    legacy_stream: Option<SseStream>,
+    next_id: u64,
+}
+
+impl HttpSession {
+    fn new(server: &McpServer) -> Result<Self, String> {
+        let url = server.url.as_deref().map(str::trim).filter(|u| !u.is_empty()).ok_or("url 不能为空")?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &server.headers {
+            let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|e| format!("请求头 {} 无效: {}", name, e))?;
+            let value = HeaderValue::from_str(value).map_err(|e| format!("请求头 {} 的值无效: {}", name, e))?;
+            headers.insert(name, value);
+        }
+        Ok(Self {
+            client: crate::http_client::build_client()?,
+            base_url: url.to_string(),
+            post_url: url.to_string(),
+            headers,
+            session_id: None,
+            protocol_version: None,
+            legacy_stream: None,
+            next_id: 1,
+        })
+    }
+
+    async fn post(&self, body: &Value) -> Result<reqwest::Response, String> {
+        let mut request = self.client.post(&self.post_url)
+            .headers(self.headers.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(body);
+        if let Some(session_id) = &self.session_id {
+            request = request.header("Mcp-Session-Id", session_id);
+        }
+        if let Some(version) = &self.protocol_version {
+            request = request.header("MCP-Protocol-Version", version);
+        }
+        request.send().await.map_err(|e| format!("请求失败: {}", e))
+    }
+
+    async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, String> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        let body: String = body.chars().take(500).collect();
+        Err(format!("HTTP {}: {}", status, body.trim()))
+    }
+
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let body = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let response = Self::check_status(self.post(&body).await?).await?;
+        self.read_response(response, id).await
+    }
+
+    /// 读取响应：旧版 SSE 传输从事件流读，streamable HTTP 的响应体是 JSON 或 SSE
+    async fn read_response(&mut self, response: reqwest::Response, id: u64) -> Result<Value, String> {
+        if let Some(stream) = self.legacy_stream.as_mut() {
+            return stream.wait_response(id).await;
+        }
+        if let Some(session_id) = response.headers().get("mcp-session-id").and_then(|v| v.to_str().ok()) {
+            self.session_id = Some(session_id.to_string());
+        }
+        let is_sse = response.headers().get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+        if is_sse {
+            return SseStream::new(response).wait_response(id).await;
+        }
+        let message: Value = response.json().await.map_err(|e| format!("解析响应失败: {}", e))?;
+        // 批量响应时取对应 id 的那一条
+        match message {
+            Value::Array(items) => items.into_iter()
+                .find(|m| is_response_to(m, id))
+                .ok_or_else(|| "响应中没有对应的结果".to_string())
+                .and_then(extract_result),
+            message => extract_result(message),
+        }
+    }
+
+    /// initialize: streamable HTTP 返回 4xx（认证失败除外）时退回旧版 SSE 传输
+    async fn initialize(&mut self, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let body = json!({"jsonrpc": "2.0", "id": id, "method": "initialize", "params": params.clone()});
+        let response = self.post(&body).await?;
+        let status = response.status().as_u16();
+        let result = if (400..500).contains(&status) && status != 401 && status != 403 {
+            println!("[McpClient] Streamable HTTP not supported (HTTP {}), falling back to SSE", status);
+            self.open_legacy_stream().await?;
+            self.request("initialize", params).await?
+        } else {
+            let response = Self::check_status(response).await?;
+            self.read_response(response, id).await?
+        };
+        self.protocol_version = result.get("protocolVersion").and_then(|v| v.as_str()).map(|s| s.to_string());
+        Ok(result)
+    }
+
+    /// 旧版 SSE 传输：GET 建立事件流，第一个 endpoint 事件给出 POST 地址
+    async fn open_legacy_stream(&mut self) -> Result<(), String> {
+        let response = self.client.get(&self.base_url)
+            .headers(self.headers.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| format!("连接事件流失败: {}", e))?;
+        let mut stream = SseStream::new(Self::check_status(response).await?);
+        loop {
+            let event = stream.next_event().await?.ok_or("事件流已关闭，未收到 endpoint 事件")?;
+            if event.event == "endpoint" {
+                let base = url::Url::parse(&self.base_url).map_err(|e| format!("url 无效: {}", e))?;
+                let endpoint = base.join(event.data.trim()).map_err(|e| format!("endpoint 无效: {}", e))?;
+                self.post_url = endpoint.to_string();
+                break;
+            }
+        }
+        self.legacy_stream = Some(stream);
+        Ok(())
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<(), String> {
+        let response = self.post(&json!({"jsonrpc": "2.0", "method": method})).await?;
+        Self::check_status(response).await.map(|_| ())
+    }
+
+    /// 结束会话（streamable HTTP 有会话 ID 时通知服务器删除，失败忽略）
+    async fn shutdown(&mut self) {
+        self.legacy_stream.take();
+        if let Some(session_id) = self.session_id.take() {
+            let _ = self.client.delete(&self.post_url)
+                .headers(self.headers.clone())
+                .header("Mcp-Session-Id", session_id)
+                .timeout(Duration::from_secs(3))
+                .send()
+                .await;
+        }
+    }
+}
+
+// ============================================================
+// 握手
+// ============================================================
+
+enum Session {
+    Stdio(StdioSession),
+    Http(HttpSession),
+}
+
+impl Session {
+    async fn initialize(&mut self, params: Value) -> Result<Value, String> {
+        match self {
+            Session::Stdio(s) => s.request("initialize", params).await,
+            Session::Http(s) => s.initialize(params).await,
+        }
+    }
+
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        match self {
+            Session::Stdio(s) => s.request(method, params).await,
+            Session::Http(s) => s.request(method, params).await,
+        }
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<(), String> {
+        match self {
+            Session::Stdio(s) => s.notify(method).await,
+            Session::Http(s) => s.notify(method).await,
+        }
+    }
+
+    /// 清理连接，返回 stderr 输出
+    async fn shutdown(&mut self) -> String {
+        match self {
+            Session::Stdio(s) => s.shutdown().await,
+            Session::Http(s) => {
+                s.shutdown().await;
+                String::new()
+            }
+        }
+    }
+}
+
+async fn handshake(session: &mut Session, result: &mut McpTestResult, started: Instant) -> Result<(), String> {
+    let init = session.initialize(json!({
+        "protocolVersion": PROTOCOL_VERSION,
+        "capabilities": {},
+        "clientInfo": {"name": "kiro-account-manager", "version": env!("CARGO_PKG_VERSION")},
+    })).await?;
+    result.initialize_ms = Some(started.elapsed().as_millis() as u64);
+    result.protocol_version = init.get("protocolVersion").and_then(|v| v.as_str()).map(|s| s.to_string());
+    result.server_info = init.get("serverInfo").cloned();
+    result.capabilities = init.get("capabilities").cloned();
+    result.instructions = init.get("instructions").and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    session.notify("notifications/initialized").await?;
+
+    // 服务器没有声明 tools 能力时不列工具
+    if result.capabilities.as_ref().is_some_and(|c| c.get("tools").is_none()) {
+        return Ok(());
+    }
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_TOOL_PAGES {
+        let params = match &cursor {
+            Some(cursor) => json!({"cursor": cursor}),
+            None => json!({}),
+        };
+        let page = session.request("tools/list", params).await?;
+        for tool in page.get("tools").and_then(|t| t.as_array()).into_iter().flatten() {
+            let Some(name) = tool.get("name").and_then(|n| n.as_str()) else { continue };
+            result.tools.push(McpTool {
+                name: name.to_string(),
+                description: tool.get("description").and_then(|d| d.as_str()).map(|s| s.to_string()),
+                input_schema: tool.get("inputSchema").cloned(),
+            });
+        }
+        cursor = page.get("nextCursor").and_then(|c| c.as_str()).map(|s| s.to_string());
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 测试服务器：握手并列出工具，超时后中止；无论成功与否都会清理启动的进程
+pub async fn test_server(server: &McpServer, timeout: Duration) -> McpTestResult {
+    let started = Instant::now();
+    let transport = server.transport();
+    let mut result = McpTestResult::new(transport);
+
+    let session = match transport {
+        Some(McpTransport::Stdio) => StdioSession::spawn(server).map(Session::Stdio),
+        Some(McpTransport::Http) => HttpSession::new(server).map(Session::Http),
+        None => Err("必须配置 command 或 url".to_string()),
+    };
+    let mut session = match session {
+        Ok(session) => session,
+        Err(e) => {
+            result.error = Some(e);
+            result.elapsed_ms = started.elapsed().as_millis() as u64;
+            return result;
+        }
+    };
+
+    let outcome = tokio::time::timeout(timeout, handshake(&mut session, &mut result, started)).await;
+    result.stderr = session.shutdown().await;
+    match outcome {
+        Ok(Ok(())) => result.success = true,
+        Ok(Err(e)) => result.error = Some(e),
+        Err(_) => result.error = Some(format!("超时（{} 秒内未完成握手和工具列表）", timeout.as_secs())),
+    }
+    result.elapsed_ms = started.elapsed().as_millis() as u64;
+    result
+}
@@ -96,7 +96,7 @@ pub struct PowerSource {
 impl PowersRegistry {
     /// 获取 Powers 目录路径
     pub fn powers_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".kiro").join("powers"))
+        crate::kiro_paths::kiro_paths().map(|p| p.powers_dir())
     }
 
     /// 获取 Powers 注册表文件路径
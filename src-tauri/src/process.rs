@@ -1,6 +1,10 @@
 // 进程管理相关功能
 
+use serde::{Deserialize, Serialize};
 use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::installations::with_installation;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -8,22 +12,54 @@ use std::os::windows::process::CommandExt;
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+#[cfg(target_os = "linux")]
+mod linux;
+
+#[cfg(target_os = "linux")]
+pub use linux::find_kiro_binary;
+
 /// 检查 Kiro IDE 是否正在运行（内部函数，同步）
+/// 目标安装的进程名（默认 Kiro.exe）
+#[cfg(target_os = "windows")]
+fn kiro_image_name() -> String {
+    crate::installations::current_installation()
+        .and_then(|i| std::path::Path::new(&i.executable).file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "Kiro.exe".to_string())
+}
+
+/// 目标安装的 .app 路径（默认 /Applications/Kiro.app）
+#[cfg(target_os = "macos")]
+fn kiro_app_path() -> std::path::PathBuf {
+    crate::installations::current_installation()
+        .map(|i| std::path::PathBuf::from(i.executable))
+        .unwrap_or_else(|| std::path::PathBuf::from("/Applications/Kiro.app"))
+}
+
 #[cfg(target_os = "windows")]
 pub fn check_kiro_running() -> bool {
+    let image_name = kiro_image_name();
     let output = Command::new("tasklist")
-        .args(["/FI", "IMAGENAME eq Kiro.exe", "/NH"])
+        .args(["/FI", &format!("IMAGENAME eq {}", image_name), "/NH"])
         .creation_flags(CREATE_NO_WINDOW)
         .output();
     
     match output {
-        Ok(out) => String::from_utf8_lossy(&out.stdout).contains("Kiro.exe"),
+        Ok(out) => String::from_utf8_lossy(&out.stdout).contains(&image_name),
         Err(_) => false
     }
 }
 
 #[cfg(target_os = "macos")]
 pub fn check_kiro_running() -> bool {
+    // 指定了安装时只匹配该 .app 内的进程
+    if crate::installations::current_installation().is_some() {
+        return Command::new("pgrep")
+            .args(["-f", &format!("{}/Contents/MacOS/", kiro_app_path().display())])
+            .output()
+            .map(|out| out.status.success())
+            .unwrap_or(false);
+    }
+    
     // 尝试多种方式检测 Kiro 进程
     // 1. 精确匹配 "Kiro"
     let output = Command::new("pgrep")
@@ -47,16 +83,21 @@ pub fn check_kiro_running() -> bool {
     }
 }
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(target_os = "linux")]
+pub fn check_kiro_running() -> bool {
+    !linux::find_kiro_pids().is_empty()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
 pub fn check_kiro_running() -> bool {
     false
 }
 
-/// 关闭 Kiro IDE（内部函数）
+/// 强制结束 Kiro IDE 进程树（内部函数，关闭流程的最后手段）
 #[cfg(target_os = "windows")]
 pub fn kill_kiro() -> Result<(), String> {
     let output = Command::new("taskkill")
-        .args(["/IM", "Kiro.exe", "/F"])
+        .args(["/IM", &kiro_image_name(), "/F", "/T"])
         .creation_flags(CREATE_NO_WINDOW)
         .output()
         .map_err(|e| format!("Failed to execute taskkill: {}", e))?;
@@ -72,12 +113,18 @@ pub fn kill_kiro() -> Result<(), String> {
 
 #[cfg(target_os = "macos")]
 pub fn kill_kiro() -> Result<(), String> {
+    // 主进程和 Helper 子进程都在 Kiro.app 内
+    let pattern = match crate::installations::current_installation() {
+        Some(_) => format!("{}/Contents/", kiro_app_path().display()),
+        None => "Kiro.app/Contents/".to_string(),
+    };
     let output = Command::new("pkill")
-        .args(["-x", "Kiro"])
+        .args(["-KILL", "-f", &pattern])
         .output()
         .map_err(|e| format!("Failed to execute pkill: {}", e))?;
     
-    if !output.status.success() {
+    // 退出码 1 表示没有匹配的进程
+    if !output.status.success() && output.status.code() != Some(1) {
         let stderr = String::from_utf8_lossy(&output.stderr);
         if !stderr.contains("No matching") {
             return Err(format!("Failed to close Kiro IDE: {}", stderr));
@@ -86,27 +133,227 @@ pub fn kill_kiro() -> Result<(), String> {
     Ok(())
 }
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(target_os = "linux")]
+pub fn kill_kiro() -> Result<(), String> {
+    linux::send_signal("KILL", &linux::find_kiro_pids())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
 pub fn kill_kiro() -> Result<(), String> {
     Err("Unsupported platform".to_string())
 }
 
+/// 请求 Kiro IDE 正常退出（不强制，IDE 可以保存状态后自行退出）
+#[cfg(target_os = "windows")]
+fn request_kiro_close() -> Result<(), String> {
+    // 不带 /F 时 taskkill 向窗口发送 WM_CLOSE
+    let output = Command::new("taskkill")
+        .args(["/IM", &kiro_image_name()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to execute taskkill: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.contains("not found") && !stderr.contains("没有找到") {
+            return Err(format!("Failed to request Kiro IDE close: {}", stderr));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn request_kiro_close() -> Result<(), String> {
+    let app_name = kiro_app_path()
+        .file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Kiro".to_string());
+    let output = Command::new("osascript")
+        .args(["-e", &format!("tell application \"{}\" to quit", app_name)])
+        .output()
+        .map_err(|e| format!("Failed to execute osascript: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Failed to request Kiro IDE close: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn request_kiro_close() -> Result<(), String> {
+    linux::send_signal("TERM", &linux::find_kiro_pids())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn request_kiro_close() -> Result<(), String> {
+    Err("Unsupported platform".to_string())
+}
+
+// ===== 关闭流程 =====
+
+/// 默认等待正常退出的时间
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+/// 强制结束后等待进程消失的时间
+const FORCE_KILL_WAIT: Duration = Duration::from_secs(3);
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 关闭结果所处阶段
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStage {
+    /// IDE 本来就没有运行
+    NotRunning,
+    /// 正常关闭请求后退出
+    Graceful,
+    /// 超时后强制结束
+    Forced,
+    /// 强制结束后仍在运行
+    Failed,
+}
+
+/// 关闭 Kiro IDE 的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownResult {
+    pub stage: ShutdownStage,
+    pub was_running: bool,
+    pub elapsed_ms: u64,
+    pub message: String,
+}
+
+impl ShutdownResult {
+    /// IDE 已经不在运行（可以安全修改 state.vscdb 等文件）
+    pub fn is_stopped(&self) -> bool {
+        self.stage != ShutdownStage::Failed
+    }
+}
+
+/// 设置中的关闭超时时间
+pub fn shutdown_timeout() -> Duration {
+    let secs = crate::commands::app_settings_cmd::load_app_settings()
+        .kiro_shutdown_timeout_secs
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// 轮询直到所有 Kiro 进程退出，超时返回 false
+fn wait_for_kiro_exit(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !check_kiro_running() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+/// 关闭 Kiro IDE: 先请求正常退出并等待，超时后才强制结束
+pub fn shutdown_kiro(timeout: Duration) -> ShutdownResult {
+    let started = Instant::now();
+    let result = |stage: ShutdownStage, message: String| ShutdownResult {
+        stage,
+        was_running: stage != ShutdownStage::NotRunning,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        message,
+    };
+
+    if !check_kiro_running() {
+        return result(ShutdownStage::NotRunning, "Kiro IDE is not running".to_string());
+    }
+
+    match request_kiro_close() {
+        Ok(()) => {
+            if wait_for_kiro_exit(timeout) {
+                return result(ShutdownStage::Graceful, "Kiro IDE exited gracefully".to_string());
+            }
+            println!("[Process] Kiro IDE still running after {}s, force killing", timeout.as_secs());
+        }
+        Err(e) => println!("[Process] Graceful close request failed: {}, force killing", e),
+    }
+
+    if let Err(e) = kill_kiro() {
+        return result(ShutdownStage::Failed, e);
+    }
+    if wait_for_kiro_exit(FORCE_KILL_WAIT) {
+        result(ShutdownStage::Forced, "Kiro IDE did not exit in time and was force killed".to_string())
+    } else {
+        result(ShutdownStage::Failed, "Kiro IDE is still running after force kill".to_string())
+    }
+}
+
+// ===== 启动 =====
+
+/// 启动参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOptions {
+    /// 打开的工作区（文件夹或 .code-workspace 文件）
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// 打开的文件
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// 额外的命令行参数（如 --new-window、--disable-extensions）
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 未指定 workspace 时打开最近使用的工作区
+    #[serde(default)]
+    pub reopen_last_workspace: bool,
+}
+
+impl LaunchOptions {
+    /// 打开指定工作区
+    pub fn with_workspace(workspace: Option<String>) -> Self {
+        Self { workspace, ..Default::default() }
+    }
+
+    /// 生成命令行参数: 选项在前，路径在后
+    fn command_args(&self) -> Result<Vec<String>, String> {
+        let mut args: Vec<String> = self.args.iter()
+            .filter(|a| !a.trim().is_empty())
+            .cloned()
+            .collect();
+
+        let workspace = match &self.workspace {
+            Some(workspace) => Some(workspace.clone()),
+            None if self.reopen_last_workspace => crate::kiro::last_kiro_workspace(),
+            None => None,
+        };
+        if let Some(workspace) = workspace.filter(|w| !w.trim().is_empty()) {
+            if !std::path::Path::new(&workspace).exists() {
+                return Err(format!("Workspace not found: {}", workspace));
+            }
+            args.push(workspace);
+        }
+        args.extend(self.files.iter().filter(|f| !f.trim().is_empty()).cloned());
+        Ok(args)
+    }
+}
+
 /// 启动 Kiro IDE（内部函数）
 #[cfg(target_os = "windows")]
-pub fn launch_kiro() -> Result<(), String> {
-    let localappdata = std::env::var("LOCALAPPDATA")
-        .map_err(|_| "Cannot find LOCALAPPDATA")?;
-    
-    let kiro_path = std::path::Path::new(&localappdata)
-        .join("Programs")
-        .join("Kiro")
-        .join("Kiro.exe");
+pub fn launch_kiro(options: &LaunchOptions) -> Result<(), String> {
+    let kiro_path = match crate::installations::current_installation() {
+        Some(installation) => std::path::PathBuf::from(installation.executable),
+        None => {
+            let localappdata = std::env::var("LOCALAPPDATA")
+                .map_err(|_| "Cannot find LOCALAPPDATA")?;
+            std::path::Path::new(&localappdata)
+                .join("Programs")
+                .join("Kiro")
+                .join("Kiro.exe")
+        }
+    };
     
     if !kiro_path.exists() {
         return Err(format!("Kiro IDE not found at: {}", kiro_path.display()));
     }
     
     Command::new(&kiro_path)
+        .args(options.command_args()?)
         .spawn()
         .map_err(|e| format!("Failed to start Kiro IDE: {}", e))?;
     
@@ -114,23 +361,45 @@ pub fn launch_kiro() -> Result<(), String> {
 }
 
 #[cfg(target_os = "macos")]
-pub fn launch_kiro() -> Result<(), String> {
-    let kiro_path = "/Applications/Kiro.app";
+pub fn launch_kiro(options: &LaunchOptions) -> Result<(), String> {
+    let kiro_path = kiro_app_path();
+    
+    if !kiro_path.exists() {
+        return Err(format!("Kiro IDE not found at: {}", kiro_path.display()));
+    }
     
-    if !std::path::Path::new(kiro_path).exists() {
-        return Err(format!("Kiro IDE not found at: {}", kiro_path));
+    let args = options.command_args()?;
+    // 优先使用应用内的命令行工具，参数和路径都能直接传递
+    let cli = kiro_path.join("Contents/Resources/app/bin/kiro");
+    if !args.is_empty() && cli.exists() {
+        Command::new(&cli)
+            .args(&args)
+            .spawn()
+            .map_err(|e| format!("Failed to start Kiro IDE: {}", e))?;
+        return Ok(());
     }
     
-    Command::new("open")
-        .args(["-a", "Kiro"])
+    // open -a Kiro <路径...> --args <选项...>
+    let (flags, paths): (Vec<String>, Vec<String>) = args.into_iter().partition(|a| a.starts_with('-'));
+    let mut command = Command::new("open");
+    command.arg("-a").arg(&kiro_path).args(&paths);
+    if !flags.is_empty() {
+        command.arg("--args").args(&flags);
+    }
+    command
         .spawn()
         .map_err(|e| format!("Failed to start Kiro IDE: {}", e))?;
     
     Ok(())
 }
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
-pub fn launch_kiro() -> Result<(), String> {
+#[cfg(target_os = "linux")]
+pub fn launch_kiro(options: &LaunchOptions) -> Result<(), String> {
+    linux::launch_kiro(&options.command_args()?)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn launch_kiro(_options: &LaunchOptions) -> Result<(), String> {
     Err("Unsupported platform".to_string())
 }
 
@@ -138,28 +407,39 @@ pub fn launch_kiro() -> Result<(), String> {
 
 /// 检查 Kiro IDE 是否正在运行
 #[tauri::command]
-pub async fn is_kiro_ide_running() -> bool {
-    tokio::task::spawn_blocking(check_kiro_running)
+pub async fn is_kiro_ide_running(installation_id: Option<String>) -> bool {
+    tokio::task::spawn_blocking(move || {
+        with_installation(installation_id.as_deref(), || Ok(check_kiro_running())).unwrap_or(false)
+    })
         .await
         .unwrap_or(false)
 }
 
-/// 关闭 Kiro IDE 进程
+/// 关闭 Kiro IDE 进程（先正常退出，超时后强制结束）
+/// timeout_secs 不传时使用设置中的 kiroShutdownTimeoutSecs，installation_id 不传时使用选中的安装
 #[tauri::command]
-pub async fn close_kiro_ide() -> Result<bool, String> {
-    tokio::task::spawn_blocking(|| {
-        let was_running = check_kiro_running();
-        kill_kiro()?;
-        Ok(was_running)
+pub async fn close_kiro_ide(timeout_secs: Option<u64>, installation_id: Option<String>) -> Result<ShutdownResult, String> {
+    tokio::task::spawn_blocking(move || {
+        with_installation(installation_id.as_deref(), || {
+            let timeout = timeout_secs.map(Duration::from_secs).unwrap_or_else(shutdown_timeout);
+            let result = shutdown_kiro(timeout);
+            if result.is_stopped() {
+                Ok(result)
+            } else {
+                Err(format!("Failed to close Kiro IDE: {}", result.message))
+            }
+        })
     })
     .await
     .map_err(|e| format!("Task failed: {}", e))?
 }
 
-/// 启动 Kiro IDE
+/// 启动 Kiro IDE（可指定工作区、文件和额外参数）
 #[tauri::command]
-pub async fn start_kiro_ide() -> Result<(), String> {
-    tokio::task::spawn_blocking(launch_kiro)
+pub async fn start_kiro_ide(options: Option<LaunchOptions>, installation_id: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        with_installation(installation_id.as_deref(), || launch_kiro(&options.unwrap_or_default()))
+    })
         .await
         .map_err(|e| format!("Task failed: {}", e))?
 }
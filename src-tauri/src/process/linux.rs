@@ -0,0 +1,299 @@
+// Linux 下的 Kiro IDE 进程管理
+// 通过扫描 /proc 查找进程（覆盖 AppImage / tarball / .deb / Flatpak 安装），
+// SIGTERM 请求退出、SIGKILL 强制结束（关闭流程见 process::shutdown_kiro）；
+// 启动时优先使用设置中的安装路径，其次自动探测
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::kiro_paths::FLATPAK_APP_IDS;
+
+
+/// 设置中配置的安装路径（可执行文件 / AppImage / 安装目录）
+fn configured_install_paths() -> Vec<PathBuf> {
+    crate::commands::app_settings_cmd::load_app_settings()
+        .kiro_install_paths
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn is_kiro_file_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    // 注意排除本程序 kiro-account-manager
+    name == "kiro" || (name.starts_with("kiro") && name.ends_with(".appimage"))
+}
+
+/// 进程匹配规则: 安装路径前缀，以及是否允许按文件名 / Flatpak ID 模糊匹配
+fn process_match_rules() -> (Vec<PathBuf>, bool) {
+    match crate::installations::current_installation() {
+        // 指定了安装时只匹配该安装目录，避免误关其他版本
+        // AppImage 运行时挂载在 /tmp/.mount_*，路径无法匹配，只能退回模糊匹配
+        Some(installation) => {
+            if installation.executable.to_lowercase().ends_with(".appimage") {
+                (vec![PathBuf::from(&installation.executable)], true)
+            } else {
+                (vec![installation.install_dir()], false)
+            }
+        }
+        None => (configured_install_paths(), true),
+    }
+}
+
+/// 判断进程是否属于 Kiro IDE
+fn is_kiro_process(exe: Option<&Path>, cmdline: &[String], install_paths: &[PathBuf], fuzzy: bool) -> bool {
+    let candidates = exe.into_iter()
+        .chain(cmdline.first().map(Path::new));
+    for path in candidates {
+        if install_paths.iter().any(|p| path.starts_with(p)) {
+            return true;
+        }
+        if fuzzy && path.file_name().and_then(|n| n.to_str()).is_some_and(is_kiro_file_name) {
+            return true;
+        }
+    }
+    if !fuzzy {
+        return false;
+    }
+    // Flatpak: bwrap / flatpak run 的命令行中带应用 ID
+    cmdline.iter().any(|arg| FLATPAK_APP_IDS.iter().any(|id| arg == id || arg.ends_with(&format!("/{}", id))))
+}
+
+/// 扫描 /proc 查找 Kiro IDE 进程（包含 Electron 子进程）
+pub fn find_kiro_pids() -> Vec<u32> {
+    let own_pid = std::process::id();
+    let (install_paths, fuzzy) = process_match_rules();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| *pid != own_pid)
+        .filter(|pid| {
+            let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+            // 其他用户的进程读不到 exe，只能看 cmdline
+            let exe = std::fs::read_link(proc_dir.join("exe")).ok();
+            let cmdline: Vec<String> = std::fs::read(proc_dir.join("cmdline"))
+                .map(|raw| {
+                    raw.split(|b| *b == 0)
+                        .filter(|part| !part.is_empty())
+                        .map(|part| String::from_utf8_lossy(part).into_owned())
+                        .collect()
+                })
+                .unwrap_or_default();
+            is_kiro_process(exe.as_deref(), &cmdline, &install_paths, fuzzy)
+        })
+        .collect()
+}
+
+/// 向进程发送信号（TERM 请求正常退出，KILL 强制结束）
+pub fn send_signal(signal: &str, pids: &[u32]) -> Result<(), String> {
+    if pids.is_empty() {
+        return Ok(());
+    }
+    let output = Command::new("kill")
+        .arg(format!("-{}", signal))
+        .args(pids.iter().map(|p| p.to_string()))
+        .output()
+        .map_err(|e| format!("Failed to execute kill: {}", e))?;
+
+    // 进程可能在发信号前已经退出，只有仍存活时才算失败
+    if !output.status.success() && !find_kiro_pids().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.contains("No such process") {
+            return Err(format!("Failed to close Kiro IDE: {}", stderr));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================
+// 启动
+// ============================================================
+
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"))
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// 安装目录中的可执行文件
+fn binary_in_dir(dir: &Path) -> Option<PathBuf> {
+    ["kiro", "Kiro", "bin/kiro"].iter()
+        .map(|name| dir.join(name))
+        .find(|p| is_executable(p))
+}
+
+/// 目录中的 Kiro AppImage（取文件名排序最后的，通常是最新版本）
+fn appimage_in_dir(dir: &Path) -> Option<PathBuf> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(dir).ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(is_kiro_file_name))
+        .filter(|p| is_executable(p))
+        .collect();
+    images.sort();
+    images.pop()
+}
+
+/// 查找 Kiro 可执行文件: 设置 > 常见安装位置 > PATH
+pub fn find_kiro_binary() -> Option<PathBuf> {
+    for path in configured_install_paths() {
+        if path.is_dir() {
+            if let Some(bin) = binary_in_dir(&path).or_else(|| appimage_in_dir(&path)) {
+                return Some(bin);
+            }
+        } else if is_executable(&path) {
+            return Some(path);
+        }
+    }
+
+    let home = home_dir();
+    let known = [
+        PathBuf::from("/usr/bin/kiro"),
+        PathBuf::from("/usr/share/kiro/kiro"),
+        PathBuf::from("/opt/Kiro/kiro"),
+        PathBuf::from("/opt/kiro/kiro"),
+        home.join(".local/bin/kiro"),
+        home.join(".local/share/kiro/kiro"),
+        home.join(".local/share/Kiro/kiro"),
+    ];
+    if let Some(bin) = known.into_iter().find(|p| is_executable(p)) {
+        return Some(bin);
+    }
+
+    for dir in [home.join("Applications"), home.join("Downloads"), home.join(".local/bin")] {
+        if let Some(image) = appimage_in_dir(&dir) {
+            return Some(image);
+        }
+    }
+
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join("kiro"))
+            .find(|p| is_executable(p))
+    })
+}
+
+/// .desktop 文件所在目录（含 Flatpak 导出目录）
+fn desktop_entry_dirs() -> Vec<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home_dir().join(".local/share"));
+    let mut dirs = vec![
+        data_home.join("applications"),
+        data_home.join("flatpak/exports/share/applications"),
+        PathBuf::from("/var/lib/flatpak/exports/share/applications"),
+    ];
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(data_dirs.split(':').filter(|d| !d.is_empty()).map(|d| PathBuf::from(d).join("applications")));
+    dirs
+}
+
+/// 从 .desktop 文件中读取 [Desktop Entry] 的 Exec
+fn desktop_entry_exec(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut in_main_section = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_section = line == "[Desktop Entry]";
+        } else if in_main_section {
+            if let Some(exec) = line.strip_prefix("Exec=") {
+                return Some(exec.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// 拆分 Exec 命令行（支持双引号），去掉 %U / %f 等字段代码
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args.into_iter()
+        .filter(|a| !(a.len() == 2 && a.starts_with('%')))
+        .collect()
+}
+
+/// 查找 Kiro 的 .desktop 启动命令
+fn find_desktop_entry_command() -> Option<Vec<String>> {
+    for dir in desktop_entry_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()).map(str::to_lowercase) else { continue };
+            if !name.ends_with(".desktop") || !name.contains("kiro") || name.contains("account-manager") {
+                continue;
+            }
+            if let Some(args) = desktop_entry_exec(&path).map(|exec| split_exec(&exec)).filter(|a| !a.is_empty()) {
+                return Some(args);
+            }
+        }
+    }
+    None
+}
+
+/// 启动 Kiro IDE，args 为传给 IDE 的命令行参数（工作区、文件、额外选项）
+pub fn launch_kiro(args: &[String]) -> Result<(), String> {
+    let target = crate::installations::current_installation().map(|i| PathBuf::from(i.executable));
+    if let Some(binary) = target.clone().filter(|p| is_executable(p)) {
+        Command::new(&binary)
+            .args(args)
+            .spawn()
+            .map_err(|e| format!("Failed to start Kiro IDE ({}): {}", binary.display(), e))?;
+        return Ok(());
+    }
+    if let Some(target) = target {
+        return Err(format!("Kiro IDE not found at: {}", target.display()));
+    }
+
+    if let Some(binary) = find_kiro_binary() {
+        Command::new(&binary)
+            .args(args)
+            .spawn()
+            .map_err(|e| format!("Failed to start Kiro IDE ({}): {}", binary.display(), e))?;
+        return Ok(());
+    }
+
+    if let Some(command) = find_desktop_entry_command() {
+        Command::new(&command[0])
+            .args(&command[1..])
+            .args(args)
+            .spawn()
+            .map_err(|e| format!("Failed to start Kiro IDE ({}): {}", command.join(" "), e))?;
+        return Ok(());
+    }
+
+    Err("Kiro IDE not found. Set the install path in settings (kiroInstallPaths)".to_string())
+}
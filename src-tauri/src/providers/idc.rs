@@ -140,6 +140,7 @@ impl AuthProvider for IdcProvider {
             client_secret: Some(client_reg.client_secret),
             client_id_hash: Some(client_id_hash),
             sso_session_id: token_response.aws_sso_app_session_id,
+            client_secret_expires_at: client_reg.client_secret_expires_at,
             profile_arn: None,
             csrf_token: None,
             session_token: None,
@@ -172,6 +173,7 @@ impl AuthProvider for IdcProvider {
             client_secret: Some(client_secret),
             client_id_hash: Some(client_id_hash),
             sso_session_id: token_response.aws_sso_app_session_id,
+            client_secret_expires_at: None,
             profile_arn: None,
             csrf_token: None,
             session_token: None,
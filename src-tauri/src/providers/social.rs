@@ -117,6 +117,7 @@ impl AuthProvider for SocialProvider {
             client_secret: None,
             client_id_hash: None,
             sso_session_id: None,
+            client_secret_expires_at: None,
             profile_arn: token_response.profile_arn,
             csrf_token: token_response.csrf_token,
             session_token: None,
@@ -143,6 +144,7 @@ impl AuthProvider for SocialProvider {
             client_secret: None,
             client_id_hash: None,
             sso_session_id: None,
+            client_secret_expires_at: None,
             profile_arn: metadata.profile_arn.or(token_response.profile_arn),
             csrf_token: token_response.csrf_token,
             session_token: None,
@@ -3,6 +3,7 @@
 // 独立于现有的 AuthDesktopService 登录
 
 use super::{AuthProvider, AuthResult, RefreshMetadata};
+use crate::http_client::{send_with_retry, RetryPolicy};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -10,8 +11,7 @@ use serde::{Deserialize, Serialize};
 // 常量配置
 // ============================================================
 
-const KIRO_WEB_PORTAL: &str = "https://app.kiro.dev";
-const KIRO_REDIRECT_URI: &str = "https://app.kiro.dev/signin/oauth";
+// KiroWebPortalService 地址和 OAuth 回调地址见 endpoints.rs（web_portal / web_redirect_uri）
 
 // ============================================================
 // CBOR 编解码
@@ -242,8 +242,9 @@ pub struct KiroWebPortalClient {
 impl KiroWebPortalClient {
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::new(),
-            endpoint: KIRO_WEB_PORTAL.to_string(),
+            client: crate::http_client::build_client()
+                .expect("Failed to create HTTP client"),
+            endpoint: crate::endpoints::endpoints().web_portal,
         }
     }
 
@@ -270,13 +271,13 @@ impl KiroWebPortalClient {
 
         let body = cbor_encode(&request)?;
 
-        let response = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/cbor")
             .header("Accept", "application/cbor")
             .header("smithy-protocol", "rpc-v2-cbor")
-            .body(body)
-            .send()
+            .body(body);
+        let response = send_with_retry(request, RetryPolicy::Idempotent, "webPortal.initiateLogin")
             .await
             .map_err(|e| format!("InitiateLogin request failed: {}", e))?;
 
@@ -324,13 +325,13 @@ impl KiroWebPortalClient {
 
         let body = cbor_encode(&request)?;
 
-        let response = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/cbor")
             .header("Accept", "application/cbor")
             .header("smithy-protocol", "rpc-v2-cbor")
-            .body(body)
-            .send()
+            .body(body);
+        let response = send_with_retry(request, RetryPolicy::NonIdempotent, "webPortal.exchangeToken")
             .await
             .map_err(|e| format!("ExchangeToken request failed: {}", e))?;
 
@@ -434,15 +435,15 @@ impl KiroWebPortalClient {
             "csrfToken": csrf_token
         })).unwrap_or_default());
 
-        let response = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/cbor")
             .header("Accept", "application/cbor")
             .header("smithy-protocol", "rpc-v2-cbor")
             .header("x-csrf-token", csrf_token)
             .header("Cookie", cookie)
-            .body(body)
-            .send()
+            .body(body);
+        let response = send_with_retry(request, RetryPolicy::NonIdempotent, "webPortal.refreshToken")
             .await
             .map_err(|e| format!("RefreshToken request failed: {}", e))?;
 
@@ -508,7 +509,7 @@ impl KiroWebPortalClient {
             "idp": idp
         })).unwrap_or_default());
 
-        let response = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/cbor")
             .header("Accept", "application/cbor")
@@ -516,8 +517,8 @@ impl KiroWebPortalClient {
             .header("authorization", format!("Bearer {}", access_token))
             // 不再需要 x-csrf-token
             .header("Cookie", cookie)
-            .body(body)
-            .send()
+            .body(body);
+        let response = send_with_retry(request, RetryPolicy::Idempotent, "webPortal.getUserInfo")
             .await
             .map_err(|e| format!("GetUserInfo request failed: {}", e))?;
 
@@ -580,7 +581,7 @@ impl KiroWebPortalClient {
             "idp": idp
         })).unwrap_or_default());
 
-        let response = self.client
+        let request = self.client
             .post(&url)
             .header("Content-Type", "application/cbor")
             .header("Accept", "application/cbor")
@@ -588,8 +589,8 @@ impl KiroWebPortalClient {
             .header("authorization", format!("Bearer {}", access_token))
             // 不再需要 x-csrf-token
             .header("Cookie", cookie)
-            .body(body)
-            .send()
+            .body(body);
+        let response = send_with_retry(request, RetryPolicy::Idempotent, "webPortal.getUserUsageAndLimits")
             .await
             .map_err(|e| format!("GetUserUsageAndLimits request failed: {}", e))?;
 
@@ -679,7 +680,7 @@ impl WebOAuthProvider {
         let state = uuid::Uuid::new_v4().to_string();
         let code_verifier = generate_code_verifier();
         let code_challenge = generate_code_challenge(&code_verifier);
-        let redirect_uri = KIRO_REDIRECT_URI.to_string();
+        let redirect_uri = crate::endpoints::endpoints().web_redirect_uri;
 
         let idp = self.get_idp_name();
         
@@ -719,11 +720,11 @@ impl WebOAuthProvider {
         // 需要传给 ExchangeToken API
 
         let idp = self.get_idp_name();
-        let redirect_uri = KIRO_REDIRECT_URI;
+        let redirect_uri = crate::endpoints::endpoints().web_redirect_uri;
 
         let client = KiroWebPortalClient::new();
         let result = client
-            .exchange_token(idp, code, code_verifier, redirect_uri, returned_state)
+            .exchange_token(idp, code, code_verifier, &redirect_uri, returned_state)
             .await?;
 
         println!("[WebOAuth] ExchangeToken Response: {}", serde_json::to_string_pretty(&serde_json::json!({
@@ -768,6 +769,7 @@ impl WebOAuthProvider {
             client_secret: None,
             client_id_hash: None,
             sso_session_id: None,
+            client_secret_expires_at: None,
             profile_arn: result.profile_arn,
             csrf_token: Some(csrf_token),  // csrfToken
             session_token: None,
@@ -811,6 +813,7 @@ impl WebOAuthProvider {
             client_secret: None,
             client_id_hash: None,
             sso_session_id: None,
+            client_secret_expires_at: None,
             profile_arn: token_response.profile_arn,
             csrf_token: Some(new_csrf_token),
             session_token: None,
@@ -0,0 +1,140 @@
+// Kiro settings.json 监听
+// 开启模型锁定（lock_model + locked_model）或配置了固定设置项（pinned_kiro_settings）时，
+// IDE 或用户改动这些键后自动改回，并发送 kiro-settings-reverted 事件通知前端。
+// 轮询文件修改时间：文件稳定一段时间后才处理（去抖），自己写入的版本直接跳过，
+// 短时间内被反复改回时暂停恢复，避免和 IDE 互相覆盖
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::app_settings_cmd::load_app_settings;
+use crate::commands::kiro_settings_cmd::{get_kiro_settings_path, is_own_write, update_kiro_settings};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 文件多久没有变化才算修改完成
+const DEBOUNCE: Duration = Duration::from_millis(1500);
+/// 时间窗口内最多恢复的次数，超过后暂停到窗口结束
+const MAX_REVERTS_PER_WINDOW: usize = 5;
+const REVERT_WINDOW: Duration = Duration::from_secs(60);
+
+const MODEL_SELECTION_KEY: &str = "kiroAgent.modelSelection";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertedSetting {
+    pub key: String,
+    /// 被外部改成的值（None 表示键被删除）
+    pub previous: Option<Value>,
+    /// 恢复后的值（null 表示删除该键）
+    pub restored: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsRevertedEvent {
+    pub reverted: Vec<RevertedSetting>,
+    pub reverted_at: String,
+}
+
+/// 当前需要保持的设置项：固定设置 + 锁定的模型
+fn pinned_settings() -> Map<String, Value> {
+    let settings = load_app_settings();
+    let mut pinned = settings.pinned_kiro_settings.unwrap_or_default();
+    if settings.lock_model.unwrap_or(false) {
+        if let Some(model) = settings.locked_model.filter(|m| !m.is_empty()) {
+            pinned.insert(MODEL_SELECTION_KEY.to_string(), Value::String(model));
+        }
+    }
+    pinned
+}
+
+/// 文件签名（修改时间 + 大小），文件不存在时为 None
+fn file_signature() -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(get_kiro_settings_path()?).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// 检查并恢复被改动的固定设置项
+fn enforce_pinned_settings() -> Result<Vec<RevertedSetting>, String> {
+    let pinned = pinned_settings();
+    if pinned.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let path = get_kiro_settings_path().ok_or("无法获取 Kiro 设置路径")?;
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    if is_own_write(&content) {
+        return Ok(Vec::new());
+    }
+    // 解析失败（比如用户正在编辑）时不处理，等下一次修改
+    let current = crate::jsonc::parse(&content)?;
+
+    let reverted: Vec<RevertedSetting> = pinned.into_iter()
+        .filter_map(|(key, expected)| {
+            let actual = current.get(&key).cloned();
+            let matches = match &actual {
+                Some(value) => *value == expected,
+                None => expected.is_null(),
+            };
+            (!matches).then_some(RevertedSetting { key, previous: actual, restored: expected })
+        })
+        .collect();
+    if reverted.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let changes: Vec<(String, Option<Value>)> = reverted.iter()
+        .map(|r| (r.key.clone(), if r.restored.is_null() { None } else { Some(r.restored.clone()) }))
+        .collect();
+    update_kiro_settings(&changes)?;
+    Ok(reverted)
+}
+
+/// 启动后台监听线程
+pub fn start(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        // 启动时也检查一次（管理器没运行期间的修改同样需要恢复）
+        let mut handled: Option<Option<(SystemTime, u64)>> = None;
+        let mut last_seen = file_signature();
+        let mut stable_since = Instant::now();
+        let mut recent_reverts: Vec<Instant> = Vec::new();
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let signature = file_signature();
+            if signature != last_seen {
+                last_seen = signature;
+                stable_since = Instant::now();
+                continue;
+            }
+            if handled == Some(signature) || stable_since.elapsed() < DEBOUNCE {
+                continue;
+            }
+            handled = Some(signature);
+
+            recent_reverts.retain(|t| t.elapsed() < REVERT_WINDOW);
+            if recent_reverts.len() >= MAX_REVERTS_PER_WINDOW {
+                // 已经写过的键又被改掉：很可能有程序在反复覆盖，暂停一段时间
+                handled = None;
+                continue;
+            }
+
+            match enforce_pinned_settings() {
+                Ok(reverted) if !reverted.is_empty() => {
+                    let keys: Vec<&str> = reverted.iter().map(|r| r.key.as_str()).collect();
+                    println!("[SettingsWatcher] Reverted external change: {}", keys.join(", "));
+                    recent_reverts.push(Instant::now());
+                    let _ = app_handle.emit("kiro-settings-reverted", SettingsRevertedEvent {
+                        reverted,
+                        reverted_at: chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string(),
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => println!("[SettingsWatcher] Skipped: {}", e),
+            }
+        }
+    });
+}
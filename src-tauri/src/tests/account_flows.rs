@@ -0,0 +1,213 @@
+// 账号添加 / 同步流程（桌面端 Social、IdC BuilderId）
+
+use super::mock_server::{self, CLIENT_SECRET_EXPIRES_AT, EXPIRED_CLIENT_ID};
+use super::{mock_app, stored_account, update_stored_account};
+use crate::aws_sso_client::{AWSSSOClient, DevicePollResult};
+use crate::commands::account_cmd::{add_account_by_idc, add_account_by_social, sync_account};
+use tauri::Manager;
+
+#[tokio::test]
+async fn add_account_by_social_stores_tokens_and_usage() {
+    let app = mock_app();
+
+    let account = add_account_by_social(app.state(), "rt-alice".to_string(), Some("Google".to_string()))
+        .await
+        .unwrap();
+
+    assert_eq!(account.email, "alice@example.com");
+    assert_eq!(account.provider.as_deref(), Some("Google"));
+    assert_eq!(account.status, "正常");
+    assert_eq!(account.access_token.as_deref(), Some("at-alice"));
+    assert_eq!(account.refresh_token.as_deref(), Some("rt-alice.1"));
+    assert_eq!(account.user_id.as_deref(), Some("user-alice"));
+    let usage = account.usage_data.expect("usage data");
+    assert_eq!(usage["usageBreakdownList"][0]["usageLimit"], 50);
+    assert!(stored_account(&app, &account.id).is_some());
+}
+
+#[tokio::test]
+async fn add_account_by_social_marks_suspended_account() {
+    let app = mock_app();
+
+    let account = add_account_by_social(app.state(), "rt-suspended-bob".to_string(), Some("Github".to_string()))
+        .await
+        .unwrap();
+
+    assert_eq!(account.status, "已封禁");
+    assert_eq!(account.usage_data, Some(serde_json::Value::Null));
+}
+
+#[tokio::test]
+async fn add_account_by_social_rejects_invalid_grant() {
+    let app = mock_app();
+
+    let err = add_account_by_social(app.state(), "rt-invalid-carol".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("400"), "unexpected error: {}", err);
+    assert!(app.state::<crate::state::AppState>().store.lock().unwrap().accounts.is_empty());
+}
+
+#[tokio::test]
+async fn usage_call_retries_after_throttling() {
+    let app = mock_app();
+
+    let account = add_account_by_social(app.state(), "rt-throttled-dave".to_string(), Some("Google".to_string()))
+        .await
+        .unwrap();
+
+    assert_eq!(account.status, "正常");
+    assert_eq!(account.email, "throttled-dave@example.com");
+    assert_eq!(mock_server::usage_calls("at-throttled-dave"), 2);
+}
+
+#[tokio::test]
+async fn sync_account_rotates_social_tokens() {
+    let app = mock_app();
+    let account = add_account_by_social(app.state(), "rt-erin".to_string(), Some("Google".to_string()))
+        .await
+        .unwrap();
+
+    let synced = sync_account(app.handle().clone(), app.state(), account.id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(synced.refresh_token.as_deref(), Some("rt-erin.2"));
+    assert_eq!(synced.status, "正常");
+    assert!(synced.expires_at.is_some());
+    assert_eq!(stored_account(&app, &account.id).unwrap().refresh_token, synced.refresh_token);
+}
+
+#[tokio::test]
+async fn sync_account_detects_suspension() {
+    let app = mock_app();
+    let account = add_account_by_social(app.state(), "rt-frank".to_string(), Some("Google".to_string()))
+        .await
+        .unwrap();
+    update_stored_account(&app, &account.id, |a| a.refresh_token = Some("rt-suspended-frank".to_string()));
+
+    let synced = sync_account(app.handle().clone(), app.state(), account.id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(synced.status, "已封禁");
+}
+
+#[tokio::test]
+async fn add_account_by_idc_stores_client_registration() {
+    let app = mock_app();
+
+    let account = add_account_by_idc(
+        app.state(),
+        "rt-grace".to_string(),
+        "mock-client".to_string(),
+        "mock-secret".to_string(),
+        Some("us-east-1".to_string()),
+        Some(CLIENT_SECRET_EXPIRES_AT),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(account.email, "grace@example.com");
+    assert_eq!(account.provider.as_deref(), Some("BuilderId"));
+    assert_eq!(account.status, "正常");
+    assert_eq!(account.client_id.as_deref(), Some("mock-client"));
+    assert_eq!(account.client_secret_expires_at, Some(CLIENT_SECRET_EXPIRES_AT));
+    assert_eq!(account.refresh_token.as_deref(), Some("rt-grace.1"));
+    assert!(account.client_id_hash.is_some());
+}
+
+#[tokio::test]
+async fn add_account_by_idc_rejects_invalid_grant() {
+    let app = mock_app();
+
+    let err = add_account_by_idc(
+        app.state(),
+        "rt-invalid-heidi".to_string(),
+        "mock-client".to_string(),
+        "mock-secret".to_string(),
+        None,
+        None,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.contains("invalid_grant"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn sync_account_refreshes_idc_account() {
+    let app = mock_app();
+    let account = add_account_by_idc(
+        app.state(),
+        "rt-ivan".to_string(),
+        "mock-client".to_string(),
+        "mock-secret".to_string(),
+        None,
+        Some(CLIENT_SECRET_EXPIRES_AT),
+    )
+    .await
+    .unwrap();
+
+    let synced = sync_account(app.handle().clone(), app.state(), account.id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(synced.access_token.as_deref(), Some("at-ivan"));
+    assert_eq!(synced.refresh_token.as_deref(), Some("rt-ivan.2"));
+    assert_eq!(synced.status, "正常");
+}
+
+#[tokio::test]
+async fn sync_account_marks_expired_idc_client() {
+    let app = mock_app();
+    let account = add_account_by_idc(
+        app.state(),
+        "rt-judy".to_string(),
+        "mock-client".to_string(),
+        "mock-secret".to_string(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    update_stored_account(&app, &account.id, |a| a.client_id = Some(EXPIRED_CLIENT_ID.to_string()));
+
+    let err = sync_account(app.handle().clone(), app.state(), account.id.clone())
+        .await
+        .unwrap_err();
+
+    assert!(err.starts_with("CLIENT_EXPIRED:"), "unexpected error: {}", err);
+    assert_eq!(stored_account(&app, &account.id).unwrap().status, "客户端已过期");
+}
+
+#[tokio::test]
+async fn idc_device_authorization_flow() {
+    mock_server::install();
+    let client = AWSSSOClient::new("us-east-1");
+
+    let registration = client.register_device_client("https://view.awsapps.com/start").await.unwrap();
+    assert_eq!(registration.client_secret_expires_at, Some(CLIENT_SECRET_EXPIRES_AT));
+
+    let device = client
+        .start_device_authorization(&registration.client_id, &registration.client_secret, "https://view.awsapps.com/start")
+        .await
+        .unwrap();
+    assert_eq!(device.user_code, "ABCD-EFGH");
+
+    let pending = client
+        .poll_device_token(&registration.client_id, &registration.client_secret, "pending")
+        .await
+        .unwrap();
+    assert!(matches!(pending, DevicePollResult::Pending));
+
+    let done = client
+        .poll_device_token(&registration.client_id, &registration.client_secret, &device.device_code)
+        .await
+        .unwrap();
+    match done {
+        DevicePollResult::Success(token) => assert_eq!(token.access_token, "at-device"),
+        other => panic!("unexpected poll result: {:?}", other),
+    }
+}
@@ -0,0 +1,380 @@
+// 本地 mock 服务：模拟 Kiro 桌面端认证、AWS SSO OIDC、CodeWhisperer 和 KiroWebPortalService
+//
+// 场景由 token 名称决定，测试之间互不干扰（token 格式: <前缀>-<名字>[.<轮换次数>]）
+//   rt-<name> / at-<name>  -> 用户 <name>@example.com
+//   名字以 suspended 开头  -> getUsageLimits 返回 403 + reason（封禁）
+//   名字以 throttled 开头  -> getUsageLimits 第一次返回 429 ThrottlingException
+//   名字以 invalid 开头    -> 刷新返回 400 invalid_grant
+//   clientId = expired-client -> OIDC 刷新返回 400 invalid_client
+//   deviceCode = pending      -> OIDC 设备轮询返回 authorization_pending
+//   ExchangeToken code 以 invalid 开头 -> 400
+//   Web Portal RefreshToken 名字以 suspended 开头 -> 423 AccountSuspendedException
+
+use crate::endpoints::{set_endpoints_override, Endpoints};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Mutex, OnceLock};
+use tiny_http::{Header, Request, Response, Server};
+
+pub const EXPIRED_CLIENT_ID: &str = "expired-client";
+pub const CLIENT_SECRET_EXPIRES_AT: i64 = 4_102_444_800; // 2100-01-01
+
+struct MockServer {
+    base_url: String,
+    usage_calls: Mutex<HashMap<String, u32>>,
+}
+
+static SERVER: OnceLock<MockServer> = OnceLock::new();
+
+/// 启动 mock 服务（进程内只启动一次），并把所有端点指向它
+pub fn install() {
+    let server = SERVER.get_or_init(|| {
+        let http = Server::http("127.0.0.1:0").expect("failed to bind mock server");
+        let addr = http.server_addr().to_ip().expect("mock server is not bound to an IP");
+        let base_url = format!("http://{}", addr);
+        std::thread::spawn(move || {
+            for request in http.incoming_requests() {
+                handle(request);
+            }
+        });
+        MockServer { base_url, usage_calls: Mutex::new(HashMap::new()) }
+    });
+    set_endpoints_override(Some(endpoints(&server.base_url)));
+}
+
+fn endpoints(base_url: &str) -> Endpoints {
+    Endpoints {
+        desktop_auth: format!("{}/desktop", base_url),
+        codewhisperer: format!("{}/codewhisperer", base_url),
+        web_portal: format!("{}/portal", base_url),
+        web_redirect_uri: format!("{}/signin/oauth", base_url),
+        sso_portal: format!("{}/sso", base_url),
+        oidc: format!("{}/oidc/{{region}}", base_url),
+    }
+}
+
+/// 某个 access token 调用 getUsageLimits 的次数
+pub fn usage_calls(access_token: &str) -> u32 {
+    SERVER.get()
+        .and_then(|s| s.usage_calls.lock().unwrap().get(access_token).copied())
+        .unwrap_or(0)
+}
+
+// ============================================================
+// 请求分发
+// ============================================================
+
+struct MockResponse {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+    headers: Vec<(&'static str, String)>,
+}
+
+impl MockResponse {
+    fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(&body).unwrap(),
+            headers: Vec::new(),
+        }
+    }
+
+    fn cbor(status: u16, body: Value) -> Self {
+        let mut buf = Vec::new();
+        ciborium::into_writer(&body, &mut buf).unwrap();
+        Self { status, content_type: "application/cbor", body: buf, headers: Vec::new() }
+    }
+
+    fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+fn handle(mut request: Request) {
+    let mut body = Vec::new();
+    let _ = request.as_reader().read_to_end(&mut body);
+    let path = request.url().split('?').next().unwrap_or_default().to_string();
+    // Authorization: Bearer <token>，Web Portal RefreshToken 只带 Cookie: AccessToken=<token>
+    let bearer = request.headers().iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer ").map(str::to_string))
+        .or_else(|| {
+            request.headers().iter()
+                .find(|h| h.field.equiv("Cookie"))
+                .and_then(|h| h.value.as_str().split("; ").find_map(|c| c.strip_prefix("AccessToken=")).map(str::to_string))
+        });
+
+    let response = route(&path, &body, bearer.as_deref());
+
+    let mut reply = Response::from_data(response.body)
+        .with_status_code(response.status)
+        .with_header(Header::from_bytes("Content-Type", response.content_type).unwrap());
+    for (name, value) in response.headers {
+        reply = reply.with_header(Header::from_bytes(name, value.as_bytes()).unwrap());
+    }
+    let _ = request.respond(reply);
+}
+
+fn route(path: &str, body: &[u8], bearer: Option<&str>) -> MockResponse {
+    if let Some(operation) = path.strip_prefix("/portal/service/KiroWebPortalService/operation/") {
+        let input: Value = ciborium::from_reader(body).unwrap_or(Value::Null);
+        return web_portal(operation, &input, bearer);
+    }
+
+    let input: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+    match path {
+        "/desktop/refreshToken" => desktop_refresh(&input),
+        "/desktop/oauth/token" => desktop_oauth_token(&input),
+        "/codewhisperer/getUsageLimits" => usage_limits(bearer),
+        p if p.starts_with("/oidc/") => {
+            let operation = p.splitn(4, '/').nth(3).unwrap_or_default();
+            oidc(operation, &input)
+        }
+        _ => MockResponse::json(404, json!({ "message": format!("no mock for {}", path) })),
+    }
+}
+
+// ============================================================
+// token 约定
+// ============================================================
+
+/// rt-alice.1 -> alice
+fn user_name(token: &str) -> &str {
+    let name = token.split_once('-').map(|(_, rest)| rest).unwrap_or(token);
+    name.split('.').next().unwrap_or(name)
+}
+
+/// 模拟 token 轮换: rt-alice -> rt-alice.1 -> rt-alice.2
+fn rotate(token: &str) -> String {
+    match token.rsplit_once('.') {
+        Some((base, n)) => format!("{}.{}", base, n.parse::<u32>().unwrap_or(0) + 1),
+        None => format!("{}.1", token),
+    }
+}
+
+fn email_of(token: &str) -> String {
+    format!("{}@example.com", user_name(token))
+}
+
+fn invalid_grant() -> MockResponse {
+    MockResponse::json(400, json!({
+        "error": "invalid_grant",
+        "error_description": "Invalid refresh token provided"
+    }))
+}
+
+// ============================================================
+// Kiro 桌面端认证服务
+// ============================================================
+
+fn desktop_refresh(input: &Value) -> MockResponse {
+    let refresh_token = input["refreshToken"].as_str().unwrap_or_default();
+    if user_name(refresh_token).starts_with("invalid") {
+        return invalid_grant();
+    }
+    MockResponse::json(200, json!({
+        "accessToken": format!("at-{}", user_name(refresh_token)),
+        "refreshToken": rotate(refresh_token),
+        "expiresIn": 3600,
+        "profileArn": "arn:aws:codewhisperer:us-east-1:000000000000:profile/MOCK",
+    }))
+}
+
+fn desktop_oauth_token(input: &Value) -> MockResponse {
+    let code = input["code"].as_str().unwrap_or_default();
+    if code.starts_with("invalid") {
+        return invalid_grant();
+    }
+    MockResponse::json(200, json!({
+        "accessToken": format!("at-{}", code),
+        "refreshToken": format!("rt-{}", code),
+        "expiresIn": 3600,
+        "profileArn": "arn:aws:codewhisperer:us-east-1:000000000000:profile/MOCK",
+    }))
+}
+
+// ============================================================
+// CodeWhisperer getUsageLimits（桌面端和 IdC 共用）
+// ============================================================
+
+fn usage_body(access_token: &str) -> Value {
+    json!({
+        "daysUntilReset": 12,
+        "nextDateReset": 1_767_225_600.0,
+        "userInfo": {
+            "email": email_of(access_token),
+            "userId": format!("user-{}", user_name(access_token)),
+        },
+        "subscriptionInfo": {
+            "subscriptionTitle": "KIRO FREE",
+            "type": "Q_DEVELOPER_STANDALONE_FREE",
+        },
+        "usageBreakdownList": [{
+            "resourceType": "CREDIT",
+            "usageLimit": 50,
+            "currentUsage": 3,
+            "usageLimitWithPrecision": 50.0,
+            "currentUsageWithPrecision": 3.0,
+            "currency": "USD",
+        }],
+    })
+}
+
+fn usage_limits(bearer: Option<&str>) -> MockResponse {
+    let Some(access_token) = bearer else {
+        return MockResponse::json(401, json!({ "message": "missing bearer token" }));
+    };
+    let calls = {
+        let server = SERVER.get().expect("mock server not installed");
+        let mut usage_calls = server.usage_calls.lock().unwrap();
+        let calls = usage_calls.entry(access_token.to_string()).or_insert(0);
+        *calls += 1;
+        *calls
+    };
+
+    let name = user_name(access_token);
+    if name.starts_with("suspended") {
+        return MockResponse::json(403, json!({
+            "message": "Your account has been suspended",
+            "reason": "TEMPORARILY_SUSPENDED",
+        }));
+    }
+    if name.starts_with("throttled") && calls == 1 {
+        return MockResponse::json(429, json!({ "message": "Rate exceeded" }))
+            .header("x-amzn-ErrorType", "ThrottlingException:http://internal.amazon.com/coral/com.amazon.coral.availability/")
+            .header("Retry-After", "0");
+    }
+    MockResponse::json(200, usage_body(access_token))
+}
+
+// ============================================================
+// AWS SSO OIDC
+// ============================================================
+
+fn oidc(operation: &str, input: &Value) -> MockResponse {
+    match operation {
+        "client/register" => MockResponse::json(200, json!({
+            "clientId": "mock-client",
+            "clientSecret": "mock-secret",
+            "clientIdIssuedAt": chrono::Utc::now().timestamp(),
+            "clientSecretExpiresAt": CLIENT_SECRET_EXPIRES_AT,
+        })),
+        "device_authorization" => MockResponse::json(200, json!({
+            "deviceCode": "device-code",
+            "userCode": "ABCD-EFGH",
+            "verificationUri": "https://device.sso.us-east-1.amazonaws.com/",
+            "verificationUriComplete": "https://device.sso.us-east-1.amazonaws.com/?user_code=ABCD-EFGH",
+            "expiresIn": 600,
+            "interval": 1,
+        })),
+        "token" => oidc_token(input),
+        _ => MockResponse::json(404, json!({ "error": "not_found" })),
+    }
+}
+
+fn oidc_token(input: &Value) -> MockResponse {
+    if input["clientId"].as_str() == Some(EXPIRED_CLIENT_ID) {
+        return MockResponse::json(400, json!({
+            "error": "invalid_client",
+            "error_description": "Client registration has expired",
+        }));
+    }
+
+    match input["grantType"].as_str().unwrap_or_default() {
+        "refresh_token" => {
+            let refresh_token = input["refreshToken"].as_str().unwrap_or_default();
+            if user_name(refresh_token).starts_with("invalid") {
+                return invalid_grant();
+            }
+            MockResponse::json(200, json!({
+                "accessToken": format!("at-{}", user_name(refresh_token)),
+                "refreshToken": rotate(refresh_token),
+                "tokenType": "Bearer",
+                "expiresIn": 3600,
+            }))
+        }
+        "urn:ietf:params:oauth:grant-type:device_code" => {
+            if input["deviceCode"].as_str() == Some("pending") {
+                return MockResponse::json(400, json!({ "error": "authorization_pending" }));
+            }
+            MockResponse::json(200, json!({
+                "accessToken": "at-device",
+                "refreshToken": "rt-device",
+                "tokenType": "Bearer",
+                "expiresIn": 3600,
+            }))
+        }
+        other => MockResponse::json(400, json!({ "error": "unsupported_grant_type", "grantType": other })),
+    }
+}
+
+// ============================================================
+// KiroWebPortalService（rpc-v2-cbor）
+// ============================================================
+
+fn web_portal(operation: &str, input: &Value, bearer: Option<&str>) -> MockResponse {
+    match operation {
+        "InitiateLogin" => MockResponse::cbor(200, json!({
+            "redirectUrl": format!(
+                "https://auth.mock/oauth2/authorize?idp={}&state={}",
+                input["idp"].as_str().unwrap_or_default(),
+                input["state"].as_str().unwrap_or_default(),
+            ),
+        })),
+        "ExchangeToken" => {
+            let code = input["code"].as_str().unwrap_or_default();
+            if code.starts_with("invalid") {
+                return MockResponse::cbor(400, json!({
+                    "__type": "com.amazon.kirowebportalservice#InvalidGrantException",
+                    "message": "Invalid authorization code",
+                }));
+            }
+            MockResponse::cbor(200, json!({
+                "accessToken": format!("at-{}", code),
+                "csrfToken": format!("csrf-{}", code),
+                "expiresIn": 3600,
+                "profileArn": "arn:aws:codewhisperer:us-east-1:000000000000:profile/MOCK",
+            }))
+            .header("Set-Cookie", format!("RefreshToken=rt-{}; Path=/; HttpOnly; Secure", code))
+            .header("Set-Cookie", format!("Idp={}; Path=/", input["idp"].as_str().unwrap_or("Google")))
+        }
+        "RefreshToken" => {
+            let Some(access_token) = bearer else {
+                return MockResponse::cbor(401, json!({ "message": "Unauthorized" }));
+            };
+            if user_name(access_token).starts_with("suspended") {
+                return MockResponse::cbor(423, json!({
+                    "__type": "com.amazon.kirowebportalservice#AccountSuspendedException",
+                    "message": "Account suspended",
+                }));
+            }
+            MockResponse::cbor(200, json!({
+                "accessToken": rotate(access_token),
+                "csrfToken": input["csrfToken"].as_str().map(rotate),
+                "expiresIn": 3600,
+            }))
+        }
+        "GetUserInfo" => {
+            let Some(access_token) = bearer else {
+                return MockResponse::cbor(401, json!({ "message": "Unauthorized" }));
+            };
+            MockResponse::cbor(200, json!({
+                "email": email_of(access_token),
+                "userId": format!("user-{}", user_name(access_token)),
+                "idp": "Google",
+                "status": "Active",
+            }))
+        }
+        "GetUserUsageAndLimits" => {
+            let Some(access_token) = bearer else {
+                return MockResponse::cbor(401, json!({ "message": "Unauthorized" }));
+            };
+            MockResponse::cbor(200, usage_body(access_token))
+        }
+        _ => MockResponse::cbor(404, json!({ "message": format!("unknown operation {}", operation) })),
+    }
+}
@@ -0,0 +1,43 @@
+// 集成测试：本地 mock 服务替代 Kiro / AWS 接口，端到端驱动账号相关命令
+
+mod account_flows;
+mod mock_server;
+mod web_oauth_flows;
+
+use crate::account::{Account, AccountStore};
+use crate::auth::AuthState;
+use crate::state::AppState;
+use std::sync::Mutex;
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::Manager;
+
+/// 创建指向 mock 服务、使用临时账号文件的测试应用
+fn mock_app() -> tauri::App<MockRuntime> {
+    mock_server::install();
+    let store_path = std::env::temp_dir()
+        .join("kiro-account-manager-tests")
+        .join(format!("{}.json", uuid::Uuid::new_v4()));
+    mock_builder()
+        .manage(AppState {
+            store: Mutex::new(AccountStore::with_path(store_path)),
+            auth: AuthState::new(),
+            pending_login: Mutex::new(None),
+        })
+        .build(mock_context(noop_assets()))
+        .expect("failed to build mock app")
+}
+
+/// 读取 store 中的账号
+fn stored_account(app: &tauri::App<MockRuntime>, id: &str) -> Option<Account> {
+    let state = app.state::<AppState>();
+    let store = state.store.lock().unwrap();
+    store.accounts.iter().find(|a| a.id == id).cloned()
+}
+
+/// 修改 store 中的账号（模拟 token 轮换、客户端过期等状态）
+fn update_stored_account(app: &tauri::App<MockRuntime>, id: &str, f: impl FnOnce(&mut Account)) {
+    let state = app.state::<AppState>();
+    let mut store = state.store.lock().unwrap();
+    let account = store.accounts.iter_mut().find(|a| a.id == id).expect("account not found");
+    f(account);
+}
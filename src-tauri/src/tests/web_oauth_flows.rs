@@ -0,0 +1,105 @@
+// Web OAuth 流程（KiroWebPortalService CBOR 接口）
+
+use super::{mock_app, stored_account};
+use crate::commands::web_oauth_cmd::{web_oauth_complete, web_oauth_initiate, web_oauth_refresh};
+use crate::state::AppState;
+use tauri::Manager;
+
+// web_oauth_initiate / web_oauth_complete 共用全局 PENDING_LOGIN，测试需串行执行
+static PENDING_LOGIN_GUARD: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn callback_url(code: &str, state: &str) -> String {
+    let redirect_uri = crate::endpoints::endpoints().web_redirect_uri;
+    format!("{}?code={}&state={}", redirect_uri, code, state)
+}
+
+#[tokio::test]
+async fn web_oauth_exchange_creates_account() {
+    let _guard = PENDING_LOGIN_GUARD.lock().await;
+    let app = mock_app();
+
+    let init = web_oauth_initiate("Google".to_string()).await.unwrap();
+    assert!(init.authorize_url.contains(&init.state));
+
+    web_oauth_complete(app.handle().clone(), app.state(), callback_url("kate", &init.state))
+        .await
+        .unwrap();
+
+    let account = {
+        let state = app.state::<AppState>();
+        let store = state.store.lock().unwrap();
+        store.accounts.iter().find(|a| a.email == "kate@example.com").cloned()
+    }
+    .expect("account not created");
+    assert_eq!(account.provider.as_deref(), Some("Google"));
+    assert_eq!(account.access_token.as_deref(), Some("at-kate"));
+    assert_eq!(account.refresh_token.as_deref(), Some("rt-kate"));
+    assert_eq!(account.csrf_token.as_deref(), Some("csrf-kate"));
+    assert_eq!(account.usage_data.as_ref().unwrap()["usageBreakdownList"][0]["currentUsage"], 3);
+}
+
+#[tokio::test]
+async fn web_oauth_exchange_rejects_invalid_code() {
+    let _guard = PENDING_LOGIN_GUARD.lock().await;
+    let app = mock_app();
+
+    let init = web_oauth_initiate("Github".to_string()).await.unwrap();
+    let err = web_oauth_complete(app.handle().clone(), app.state(), callback_url("invalid-code", &init.state))
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("ExchangeToken failed"), "unexpected error: {}", err);
+    assert!(app.state::<AppState>().store.lock().unwrap().accounts.is_empty());
+}
+
+#[tokio::test]
+async fn web_oauth_complete_requires_pending_login() {
+    let _guard = PENDING_LOGIN_GUARD.lock().await;
+    let app = mock_app();
+
+    let err = web_oauth_complete(app.handle().clone(), app.state(), callback_url("leo", "no-such-state"))
+        .await
+        .unwrap_err();
+
+    assert!(err.contains("No pending authentication state"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn web_oauth_refresh_rotates_tokens() {
+    let _guard = PENDING_LOGIN_GUARD.lock().await;
+    let app = mock_app();
+    let init = web_oauth_initiate("Google".to_string()).await.unwrap();
+    web_oauth_complete(app.handle().clone(), app.state(), callback_url("mallory", &init.state))
+        .await
+        .unwrap();
+    let account_id = {
+        let state = app.state::<AppState>();
+        let store = state.store.lock().unwrap();
+        store.accounts[0].id.clone()
+    };
+
+    let refreshed = web_oauth_refresh(app.state(), account_id.clone()).await.unwrap();
+
+    assert_eq!(refreshed.access_token.as_deref(), Some("at-mallory.1"));
+    assert_eq!(refreshed.csrf_token.as_deref(), Some("csrf-mallory.1"));
+    assert_eq!(stored_account(&app, &account_id).unwrap().access_token, refreshed.access_token);
+}
+
+#[tokio::test]
+async fn web_oauth_refresh_reports_suspension() {
+    let _guard = PENDING_LOGIN_GUARD.lock().await;
+    let app = mock_app();
+    let init = web_oauth_initiate("Google".to_string()).await.unwrap();
+    web_oauth_complete(app.handle().clone(), app.state(), callback_url("suspended-nina", &init.state))
+        .await
+        .unwrap();
+    let account_id = {
+        let state = app.state::<AppState>();
+        let store = state.store.lock().unwrap();
+        store.accounts[0].id.clone()
+    };
+
+    let err = web_oauth_refresh(app.state(), account_id).await.unwrap_err();
+
+    assert!(err.starts_with("BANNED:"), "unexpected error: {}", err);
+}
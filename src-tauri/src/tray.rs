@@ -0,0 +1,189 @@
+// 系统托盘
+// 菜单列出所有账号（剩余额度、到期时间，勾选当前 IDE 使用的账号），以及刷新全部、打开 IDE、退出。
+// 切换和刷新需要走前端流程（占用检查、机器码绑定、进度展示），这里只显示窗口并发事件:
+// tray-switch-account（账号 ID）、tray-refresh-all；账号变化后前端调用 refresh_tray_menu 重建菜单
+
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::account::Account;
+use crate::state::AppState;
+
+/// tauri.conf.json 中 trayIcon 的 id
+const TRAY_ID: &str = "main";
+/// 顶层菜单最多显示的账号数，其余放到子菜单
+const MAX_TOP_LEVEL_ACCOUNTS: usize = 15;
+const ACCOUNT_ID_PREFIX: &str = "account:";
+
+/// 从 usage_data 计算 (已用, 总额度)，规则与前端 utils/accountStats 一致
+fn quota(account: &Account) -> Option<(f64, f64)> {
+    let usage = account.usage_data.as_ref()?;
+    let breakdown = usage.pointer("/usageBreakdownList/0")
+        .or_else(|| usage.pointer("/usage_breakdown_list/0"))
+        .or_else(|| usage.get("usageBreakdown"))?;
+    let number = |value: Option<&serde_json::Value>| value.and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let field = |obj: &serde_json::Value, camel: &str, snake: &str| number(obj.get(camel).or_else(|| obj.get(snake)));
+
+    let mut used = field(breakdown, "currentUsage", "current_usage");
+    let mut limit = breakdown.get("usageLimit").or_else(|| breakdown.get("usage_limit")).and_then(|v| v.as_f64()).unwrap_or(50.0);
+    if let Some(trial) = breakdown.get("freeTrialInfo").or_else(|| breakdown.get("free_trial_info")) {
+        used += field(trial, "currentUsage", "current_usage");
+        limit += field(trial, "usageLimit", "usage_limit");
+    }
+    for bonus in breakdown.get("bonuses").and_then(|v| v.as_array()).into_iter().flatten() {
+        used += field(bonus, "currentUsage", "current_usage");
+        limit += field(bonus, "usageLimit", "usage_limit");
+    }
+    Some((used, limit))
+}
+
+/// 到期时间: 试用到期时间，没有时取额度重置时间（Unix 秒）
+fn expiry(account: &Account) -> Option<String> {
+    let usage = account.usage_data.as_ref()?;
+    let breakdown = usage.pointer("/usageBreakdownList/0")?;
+    let (label, ts) = match breakdown.pointer("/freeTrialInfo/freeTrialExpiry").and_then(|v| v.as_f64()) {
+        Some(ts) => ("expires", ts),
+        None => ("resets", breakdown.get("nextDateReset").and_then(|v| v.as_f64())?),
+    };
+    let date = chrono::DateTime::from_timestamp(ts as i64, 0)?
+        .with_timezone(&chrono::Local)
+        .format("%Y/%m/%d");
+    Some(format!("{} {}", label, date))
+}
+
+fn account_label(account: &Account) -> String {
+    let mut parts = vec![account.email.clone()];
+    if let Some((used, limit)) = quota(account) {
+        parts.push(format!("{:.0}/{:.0} left", (limit - used).max(0.0), limit));
+    }
+    if let Some(expiry) = expiry(account) {
+        parts.push(expiry);
+    }
+    if account.status != "正常" {
+        parts.push(format!("[{}]", account.status));
+    }
+    parts.join("  ·  ")
+}
+
+fn account_item<R: Runtime>(app: &AppHandle<R>, account: &Account, active: bool) -> tauri::Result<CheckMenuItem<R>> {
+    CheckMenuItem::with_id(
+        app,
+        format!("{}{}", ACCOUNT_ID_PREFIX, account.id),
+        account_label(account),
+        true,
+        active,
+        None::<&str>,
+    )
+}
+
+fn build_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let accounts = app.state::<AppState>().store.lock().unwrap().get_all();
+    // 当前账号: refresh token 与 IDE 本地 Token 一致
+    let active_token = crate::kiro::get_kiro_local_token().and_then(|t| t.refresh_token);
+    let is_active = |a: &Account| active_token.is_some() && a.refresh_token == active_token;
+
+    let menu = Menu::new(app)?;
+    if accounts.is_empty() {
+        menu.append(&MenuItem::with_id(app, "no_accounts", "No accounts", false, None::<&str>)?)?;
+    }
+    let split = accounts.len().min(MAX_TOP_LEVEL_ACCOUNTS);
+    for account in &accounts[..split] {
+        menu.append(&account_item(app, account, is_active(account))?)?;
+    }
+    if split < accounts.len() {
+        let more = Submenu::with_id(app, "more_accounts", format!("More accounts ({})", accounts.len() - split), true)?;
+        for account in &accounts[split..] {
+            more.append(&account_item(app, account, is_active(account))?)?;
+        }
+        menu.append(&more)?;
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "refresh_all", "Refresh all accounts", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, "open_ide", "Open Kiro IDE", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, "show_window", "Show window", true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+pub fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    let id = event.id.as_ref();
+    if let Some(account_id) = id.strip_prefix(ACCOUNT_ID_PREFIX) {
+        show_main_window(app);
+        let _ = app.emit("tray-switch-account", account_id.to_string());
+        // CheckMenuItem 点击后会自动切换勾选状态，重建菜单恢复为真实状态
+        refresh_menu(app);
+        return;
+    }
+    match id {
+        "refresh_all" => {
+            let _ = app.emit("tray-refresh-all", ());
+        }
+        "open_ide" => {
+            tauri::async_runtime::spawn_blocking(|| {
+                if let Err(e) = crate::process::launch_kiro(&crate::process::LaunchOptions::default()) {
+                    println!("[Tray] Failed to start Kiro IDE: {}", e);
+                }
+            });
+        }
+        "show_window" => show_main_window(app),
+        "quit" => app.exit(0),
+        _ => {}
+    }
+}
+
+/// 重建托盘菜单（账号列表、额度或当前账号变化后调用）
+pub fn refresh_menu<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else { return };
+    match build_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => println!("[Tray] Failed to build menu: {}", e),
+    }
+}
+
+/// 初始化托盘: 使用配置文件创建的托盘图标，没有时新建
+pub fn init<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let tray: TrayIcon<R> = match app.tray_by_id(TRAY_ID) {
+        Some(tray) => tray,
+        None => {
+            let mut builder = TrayIconBuilder::with_id(TRAY_ID).tooltip("Kiro Account Manager");
+            if let Some(icon) = app.default_window_icon() {
+                builder = builder.icon(icon.clone());
+            }
+            builder.build(app)?
+        }
+    };
+
+    tray.set_menu(Some(build_menu(app)?))?;
+    // 左键显示窗口，右键弹出菜单
+    tray.set_show_menu_on_left_click(false)?;
+    tray.on_menu_event(handle_menu_event);
+    tray.on_tray_icon_event(|tray, event| {
+        if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+            show_main_window(tray.app_handle());
+        }
+    });
+    Ok(())
+}
+
+/// 是否开启了关闭窗口时最小化到托盘
+pub fn close_to_tray_enabled() -> bool {
+    crate::commands::app_settings_cmd::load_app_settings().close_to_tray.unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn refresh_tray_menu(app_handle: AppHandle) {
+    refresh_menu(&app_handle);
+}
@@ -1,5 +1,6 @@
 import { useState, useCallback, useMemo, useEffect, useRef } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { useTheme } from '../../contexts/ThemeContext'
 import { useDialog } from '../../contexts/DialogContext'
 import { useI18n } from '../../i18n'
@@ -288,6 +289,26 @@ function AccountManager() {
     })
   }, [t])
 
+  // 系统托盘: 账号或当前 Token 变化后重建菜单
+  useEffect(() => {
+    invoke('refresh_tray_menu').catch(() => {})
+  }, [accounts, localToken])
+
+  // 系统托盘: 点击账号走与列表相同的切换确认流程，刷新全部复用自动刷新
+  useEffect(() => {
+    const unlistenSwitch = listen('tray-switch-account', (event) => {
+      const account = accounts.find(a => a.id === event.payload)
+      if (account) handleSwitchAccount(account)
+    })
+    const unlistenRefresh = listen('tray-refresh-all', () => {
+      if (accounts.length > 0) autoRefreshAll(accounts, true)
+    })
+    return () => {
+      unlistenSwitch.then(fn => fn())
+      unlistenRefresh.then(fn => fn())
+    }
+  }, [accounts, handleSwitchAccount, autoRefreshAll])
+
   // 确认切换
   const confirmSwitch = useCallback(async () => {
     const account = switchDialog?.account
@@ -365,7 +386,8 @@ function AccountManager() {
         provider: account.provider || 'Google',
         authMethod,
         resetMachineId: shouldResetMachineId,
-        autoRestart: false
+        autoRestart: false,
+        settingsProfileId: account.settingsProfileId || null
       }
       
       if (isIdC) {
@@ -373,6 +395,7 @@ function AccountManager() {
         params.region = account.region || 'us-east-1'
         params.clientId = account.clientId || null
         params.clientSecret = account.clientSecret || null
+        params.clientSecretExpiresAt = account.clientSecretExpiresAt || null
       } else {
         params.profileArn = account.profileArn || 'arn:aws:codewhisperer:us-east-1:699475941385:profile/EHGA3GRVQMUK'
       }
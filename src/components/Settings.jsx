@@ -1,7 +1,7 @@
 import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { emit } from '@tauri-apps/api/event'
-import { Lock, Copy, Sun, Moon, Palette, Check, RefreshCw, Settings as SettingsIcon, Clock, Globe, Search, Shield, Download, Upload, Shuffle, AlertTriangle } from 'lucide-react'
+import { Lock, Copy, Sun, Moon, Palette, Check, RefreshCw, Settings as SettingsIcon, Clock, Globe, Search, Shield, Download, Upload, Shuffle, AlertTriangle, Minimize2 } from 'lucide-react'
 import { useTheme } from '../contexts/ThemeContext'
 import { useDialog } from '../contexts/DialogContext'
 import { useI18n } from '../i18n.jsx'
@@ -17,6 +17,7 @@ function Settings() {
   const [autoRefresh, setAutoRefresh] = useState(true)
   const [autoRefreshInterval, setAutoRefreshInterval] = useState(50) // 分钟
   const [autoChangeMachineId, setAutoChangeMachineId] = useState(false)
+  const [closeToTray, setCloseToTray] = useState(false)
   const [bindMachineIdToAccount, setBindMachineIdToAccount] = useState(false)
   const [httpProxy, setHttpProxy] = useState('')
   const [originalProxy, setOriginalProxy] = useState('') // 原始代理值，用于判断是否修改
@@ -67,6 +68,7 @@ function Settings() {
         setAutoRefresh(appSettings.autoRefresh ?? true)
         setAutoRefreshInterval(appSettings.autoRefreshInterval ?? 50)
         setAutoChangeMachineId(appSettings.autoChangeMachineId ?? false)
+        setCloseToTray(appSettings.closeToTray ?? false)
         setBindMachineIdToAccount(appSettings.bindMachineIdToAccount ?? false)
         const browser = appSettings.browserPath || ''
         setBrowserPath(browser)
@@ -151,6 +153,11 @@ function Settings() {
     await saveAppSettings({ autoChangeMachineId: checked })
   }
 
+  const handleCloseToTrayChange = async (checked) => {
+    setCloseToTray(checked)
+    await saveAppSettings({ closeToTray: checked })
+  }
+
   const handleBindMachineIdChange = async (checked) => {
     setBindMachineIdToAccount(checked)
     await saveAppSettings({ bindMachineIdToAccount: checked })
@@ -519,6 +526,20 @@ function Settings() {
               </div>
             </label>
           )}
+
+          <label className={`flex items-start gap-3 cursor-pointer ${isDark ? 'bg-white/5 hover:bg-white/10' : 'bg-gray-50 hover:bg-gray-100'} rounded-xl p-4 transition-all hover:scale-[1.01] mt-3`}>
+            <input
+              type="checkbox"
+              checked={closeToTray}
+              onChange={(e) => handleCloseToTrayChange(e.target.checked)}
+              className="mt-0.5 w-4 h-4 rounded-lg border-gray-300 text-blue-500 focus:ring-blue-500"
+            />
+            <Minimize2 size={16} className={`${colors.textMuted} mt-0.5 flex-shrink-0`} />
+            <div>
+              <span className={`text-sm font-medium ${colors.text}`}>{t('settings.closeToTray')}</span>
+              <p className={`text-xs ${colors.textMuted} mt-0.5`}>{t('settings.closeToTrayDesc')}</p>
+            </div>
+          </label>
         </section>
 
         {/* 浏览器设置 */}